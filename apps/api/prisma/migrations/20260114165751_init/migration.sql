@@ -1,89 +0,0 @@
--- CreateEnum
-CREATE TYPE "MessageRole" AS ENUM ('user', 'assistant', 'system');
-
--- CreateEnum
-CREATE TYPE "AttachmentKind" AS ENUM ('image', 'file');
-
--- CreateEnum
-CREATE TYPE "ThinkingLevel" AS ENUM ('low', 'medium', 'high', 'xhigh');
-
--- CreateTable
-CREATE TABLE "User" (
-    "id" TEXT NOT NULL,
-    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
-    "systemPrompt" TEXT,
-
-    CONSTRAINT "User_pkey" PRIMARY KEY ("id")
-);
-
--- CreateTable
-CREATE TABLE "ChatThread" (
-    "id" TEXT NOT NULL,
-    "title" TEXT,
-    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
-    "updatedAt" TIMESTAMP(3) NOT NULL,
-    "userId" TEXT NOT NULL,
-    "totalCost" DOUBLE PRECISION NOT NULL DEFAULT 0,
-
-    CONSTRAINT "ChatThread_pkey" PRIMARY KEY ("id")
-);
-
--- CreateTable
-CREATE TABLE "Model" (
-    "id" TEXT NOT NULL,
-    "label" TEXT NOT NULL,
-    "inputCostPerToken" DOUBLE PRECISION NOT NULL,
-    "outputCostPerToken" DOUBLE PRECISION NOT NULL,
-    "supportsVision" BOOLEAN NOT NULL DEFAULT false,
-    "supportsThinkingLevels" BOOLEAN NOT NULL DEFAULT false,
-    "isActive" BOOLEAN NOT NULL DEFAULT true,
-
-    CONSTRAINT "Model_pkey" PRIMARY KEY ("id")
-);
-
--- CreateTable
-CREATE TABLE "Message" (
-    "id" TEXT NOT NULL,
-    "threadId" TEXT NOT NULL,
-    "role" "MessageRole" NOT NULL,
-    "content" TEXT NOT NULL,
-    "modelId" TEXT,
-    "thinkingLevel" "ThinkingLevel",
-    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
-    "durationMs" INTEGER,
-    "promptTokens" INTEGER,
-    "completionTokens" INTEGER,
-    "cost" DOUBLE PRECISION NOT NULL DEFAULT 0,
-
-    CONSTRAINT "Message_pkey" PRIMARY KEY ("id")
-);
-
--- CreateTable
-CREATE TABLE "Attachment" (
-    "id" TEXT NOT NULL,
-    "threadId" TEXT NOT NULL,
-    "messageId" TEXT,
-    "filename" TEXT NOT NULL,
-    "path" TEXT NOT NULL,
-    "mimeType" TEXT NOT NULL,
-    "size" INTEGER NOT NULL,
-    "kind" "AttachmentKind" NOT NULL,
-    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
-
-    CONSTRAINT "Attachment_pkey" PRIMARY KEY ("id")
-);
-
--- AddForeignKey
-ALTER TABLE "ChatThread" ADD CONSTRAINT "ChatThread_userId_fkey" FOREIGN KEY ("userId") REFERENCES "User"("id") ON DELETE RESTRICT ON UPDATE CASCADE;
-
--- AddForeignKey
-ALTER TABLE "Message" ADD CONSTRAINT "Message_threadId_fkey" FOREIGN KEY ("threadId") REFERENCES "ChatThread"("id") ON DELETE RESTRICT ON UPDATE CASCADE;
-
--- AddForeignKey
-ALTER TABLE "Message" ADD CONSTRAINT "Message_modelId_fkey" FOREIGN KEY ("modelId") REFERENCES "Model"("id") ON DELETE SET NULL ON UPDATE CASCADE;
-
--- AddForeignKey
-ALTER TABLE "Attachment" ADD CONSTRAINT "Attachment_threadId_fkey" FOREIGN KEY ("threadId") REFERENCES "ChatThread"("id") ON DELETE RESTRICT ON UPDATE CASCADE;
-
--- AddForeignKey
-ALTER TABLE "Attachment" ADD CONSTRAINT "Attachment_messageId_fkey" FOREIGN KEY ("messageId") REFERENCES "Message"("id") ON DELETE SET NULL ON UPDATE CASCADE;
@@ -0,0 +1,114 @@
+-- CreateTable
+CREATE TABLE "User" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "localUserKey" TEXT,
+    "email" TEXT,
+    "firstName" TEXT,
+    "lastName" TEXT,
+    "imageUrl" TEXT,
+    "createdAt" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updatedAt" DATETIME NOT NULL,
+    "lastSignInAt" DATETIME,
+    "systemPrompt" TEXT,
+    "openRouterApiKey" TEXT,
+    "braveSearchApiKey" TEXT,
+    "defaultModelId" TEXT,
+    "defaultThinkingLevel" TEXT,
+    "enabledModelIds" TEXT NOT NULL DEFAULT '[]',
+    "enabledTools" TEXT NOT NULL DEFAULT '["web_search","code_interpreter","memory"]',
+    "hideCostPerMessage" BOOLEAN NOT NULL DEFAULT false,
+    "fontFamily" TEXT NOT NULL DEFAULT 'Space Mono',
+    "fontSize" TEXT NOT NULL DEFAULT 'medium'
+);
+
+-- CreateTable
+CREATE TABLE "ChatThread" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "title" TEXT,
+    "createdAt" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updatedAt" DATETIME NOT NULL,
+    "userId" TEXT NOT NULL,
+    "totalCost" REAL NOT NULL DEFAULT 0,
+    "memoryCheckedAt" DATETIME,
+    CONSTRAINT "ChatThread_userId_fkey" FOREIGN KEY ("userId") REFERENCES "User" ("id") ON DELETE RESTRICT ON UPDATE CASCADE
+);
+
+-- CreateTable
+CREATE TABLE "Model" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "label" TEXT NOT NULL,
+    "inputCostPerToken" REAL NOT NULL,
+    "outputCostPerToken" REAL NOT NULL,
+    "supportsVision" BOOLEAN NOT NULL DEFAULT false,
+    "supportsThinkingLevels" BOOLEAN NOT NULL DEFAULT false,
+    "isActive" BOOLEAN NOT NULL DEFAULT true
+);
+
+-- CreateTable
+CREATE TABLE "Message" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "threadId" TEXT NOT NULL,
+    "role" TEXT NOT NULL,
+    "content" TEXT NOT NULL,
+    "modelId" TEXT,
+    "thinkingLevel" TEXT,
+    "createdAt" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "durationMs" INTEGER,
+    "promptTokens" INTEGER,
+    "completionTokens" INTEGER,
+    "cost" REAL NOT NULL DEFAULT 0,
+    "trace" TEXT,
+    "sources" TEXT,
+    CONSTRAINT "Message_threadId_fkey" FOREIGN KEY ("threadId") REFERENCES "ChatThread" ("id") ON DELETE RESTRICT ON UPDATE CASCADE,
+    CONSTRAINT "Message_modelId_fkey" FOREIGN KEY ("modelId") REFERENCES "Model" ("id") ON DELETE SET NULL ON UPDATE CASCADE
+);
+
+-- CreateTable
+CREATE TABLE "Attachment" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "threadId" TEXT NOT NULL,
+    "messageId" TEXT,
+    "filename" TEXT NOT NULL,
+    "path" TEXT NOT NULL,
+    "mimeType" TEXT NOT NULL,
+    "size" INTEGER NOT NULL,
+    "kind" TEXT NOT NULL,
+    "createdAt" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT "Attachment_threadId_fkey" FOREIGN KEY ("threadId") REFERENCES "ChatThread" ("id") ON DELETE RESTRICT ON UPDATE CASCADE,
+    CONSTRAINT "Attachment_messageId_fkey" FOREIGN KEY ("messageId") REFERENCES "Message" ("id") ON DELETE SET NULL ON UPDATE CASCADE
+);
+
+-- CreateTable
+CREATE TABLE "ActiveStream" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "threadId" TEXT NOT NULL,
+    "userMessageId" TEXT NOT NULL,
+    "assistantMessageId" TEXT,
+    "status" TEXT NOT NULL DEFAULT 'active',
+    "partialContent" TEXT NOT NULL DEFAULT '',
+    "partialTrace" TEXT,
+    "modelId" TEXT NOT NULL,
+    "thinkingLevel" TEXT,
+    "startedAt" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "lastActivityAt" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "completedAt" DATETIME,
+    CONSTRAINT "ActiveStream_threadId_fkey" FOREIGN KEY ("threadId") REFERENCES "ChatThread" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "User_localUserKey_key" ON "User"("localUserKey");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "User_email_key" ON "User"("email");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "ActiveStream_userMessageId_key" ON "ActiveStream"("userMessageId");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "ActiveStream_assistantMessageId_key" ON "ActiveStream"("assistantMessageId");
+
+-- CreateIndex
+CREATE INDEX "ActiveStream_threadId_idx" ON "ActiveStream"("threadId");
+
+-- CreateIndex
+CREATE INDEX "ActiveStream_status_lastActivityAt_idx" ON "ActiveStream"("status", "lastActivityAt");
@@ -8,6 +8,7 @@ import { ChatService } from './services/chatService';
 import { ChatRepository } from './repositories/types';
 import { MemoryStore } from './services/memoryStore';
 import { MemoryExtractor } from './services/memoryExtractor';
+import { corsMiddleware, hostTokenMiddleware } from './middleware/hostAuth';
 import { getUserId, localUserMiddleware } from './middleware/localUser';
 import { StreamTracker } from './services/streamTracker';
 
@@ -67,6 +68,8 @@ export function createApp({
   memoryExtractor,
   traceRetentionDays,
   streamTracker,
+  apiToken,
+  corsOrigins,
 }: {
   repo: ChatRepository;
   chatService: ChatService;
@@ -75,9 +78,12 @@ export function createApp({
   memoryExtractor?: MemoryExtractor;
   traceRetentionDays?: number;
   streamTracker?: StreamTracker;
+  apiToken?: string;
+  corsOrigins?: string[];
 }) {
   const app = express();
 
+  app.use(corsMiddleware(corsOrigins));
   app.use(express.json({ limit: '10mb' }));
 
   const upload = multer({
@@ -97,6 +103,9 @@ export function createApp({
     res.json({ ok: true });
   });
 
+  // Everything past health requires the host's token, when one is set
+  app.use('/api', hostTokenMiddleware(apiToken));
+
   // Local-only user context for all API routes
   app.use('/api', localUserMiddleware(repo));
 
@@ -2,8 +2,12 @@ import path from 'path';
 import dotenv from 'dotenv';
 import { z } from 'zod';
 
-dotenv.config({ path: path.resolve(process.cwd(), '.env') });
-dotenv.config({ path: path.resolve(process.cwd(), '../../.env') });
+// The desktop host passes a complete, explicit environment; reading .env files
+// on top of it would make the sidecar depend on whatever sits next to it.
+if (!process.env.PRO_CHAT_MANAGED_ENV) {
+  dotenv.config({ path: path.resolve(process.cwd(), '.env') });
+  dotenv.config({ path: path.resolve(process.cwd(), '../../.env') });
+}
 
 const envSchema = z.object({
   NODE_ENV: z.string().optional(),
@@ -13,6 +17,11 @@ const envSchema = z.object({
   DATABASE_URL: z.string().min(1),
   STORAGE_PATH: z.string().default('storage'),
   MEMORY_PATH: z.string().optional(),
+  // Host auth: bearer token required on /api, extra origins allowed by CORS
+  PRO_CHAT_API_TOKEN: z.string().optional(),
+  CORS_ORIGINS: z.string().optional(),
+  // Listen on this Unix socket instead of PORT (set by the desktop host)
+  PRO_CHAT_API_SOCKET: z.string().optional(),
   // Web fetch tool
   WEB_FETCH_ALLOW_DOMAINS: z.string().optional(),
   WEB_FETCH_DENY_DOMAINS: z.string().optional(),
@@ -38,6 +47,9 @@ export const env = envSchema.parse({
   DATABASE_URL: process.env.DATABASE_URL,
   STORAGE_PATH: process.env.STORAGE_PATH,
   MEMORY_PATH: process.env.MEMORY_PATH,
+  PRO_CHAT_API_TOKEN: process.env.PRO_CHAT_API_TOKEN,
+  CORS_ORIGINS: process.env.CORS_ORIGINS,
+  PRO_CHAT_API_SOCKET: process.env.PRO_CHAT_API_SOCKET,
   WEB_FETCH_ALLOW_DOMAINS: process.env.WEB_FETCH_ALLOW_DOMAINS,
   WEB_FETCH_DENY_DOMAINS: process.env.WEB_FETCH_DENY_DOMAINS,
   WEB_FETCH_MAX_REDIRECTS: process.env.WEB_FETCH_MAX_REDIRECTS,
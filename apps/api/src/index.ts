@@ -89,6 +89,10 @@ const app = createApp({
   memoryExtractor,
   traceRetentionDays: env.TRACE_RETENTION_DAYS,
   streamTracker,
+  apiToken: env.PRO_CHAT_API_TOKEN,
+  corsOrigins: env.CORS_ORIGINS?.split(',')
+    .map((origin) => origin.trim())
+    .filter(Boolean),
 });
 
 const resolveSqlitePath = (databaseUrl: string, schemaDir: string): string => {
@@ -111,7 +115,9 @@ const resolvePrismaCli = (): string => {
 };
 
 async function bootstrap() {
-  if (env.DATABASE_URL.startsWith('file:')) {
+  // Under the desktop host the schema is already migrated before the API starts;
+  // only a standalone API creates its own database.
+  if (!process.env.PRO_CHAT_MANAGED_ENV && env.DATABASE_URL.startsWith('file:')) {
     const schemaPath = path.resolve(process.cwd(), 'prisma', 'schema.prisma');
     const schemaDir = path.dirname(schemaPath);
     const dbPath = resolveSqlitePath(env.DATABASE_URL, schemaDir);
@@ -135,9 +141,34 @@ async function bootstrap() {
   // Start background cleanup job for stale streams
   startStreamCleanupJob(streamTracker);
 
-  app.listen(env.PORT, () => {
-    console.log(`API listening on http://localhost:${env.PORT}`);
-  });
+  const socketPath = env.PRO_CHAT_API_SOCKET;
+  if (socketPath) {
+    // A socket file left by a crashed run would make listen fail with EADDRINUSE.
+    fs.rmSync(socketPath, { force: true });
+  }
+  const server = socketPath
+    ? app.listen(socketPath, () => {
+        fs.chmodSync(socketPath, 0o600);
+        console.log(`API listening on ${socketPath}`);
+      })
+    : app.listen(env.PORT, () => {
+        console.log(`API listening on http://localhost:${env.PORT}`);
+      });
+
+  // The desktop host sends SIGTERM before falling back to a hard kill, so flush
+  // in-flight stream progress and close the database cleanly.
+  const shutdown = (signal: NodeJS.Signals) => {
+    console.log(`Received ${signal}, shutting down`);
+    server.close();
+    streamTracker
+      .flushAll()
+      .catch((error) => console.error('Failed to flush streams during shutdown', error))
+      .finally(() => {
+        repository.client.$disconnect().finally(() => process.exit(0));
+      });
+  };
+  process.once('SIGTERM', shutdown);
+  process.once('SIGINT', shutdown);
 }
 
 bootstrap().catch((error) => {
@@ -0,0 +1,60 @@
+import { timingSafeEqual } from 'crypto';
+import type { Request, Response, NextFunction } from 'express';
+
+// Origins the desktop webview loads from (macOS/Linux, then Windows).
+export const DESKTOP_ORIGINS = [
+  'tauri://localhost',
+  'http://tauri.localhost',
+  'https://tauri.localhost',
+];
+
+/**
+ * Lets the desktop webview (and any `extraOrigins`) call the API cross-origin.
+ * Other origins get no CORS headers, so browsers keep hiding responses from
+ * unrelated pages.
+ */
+export function corsMiddleware(extraOrigins: string[] = []) {
+  const allowed = new Set([...DESKTOP_ORIGINS, ...extraOrigins]);
+  return (req: Request, res: Response, next: NextFunction) => {
+    const origin = req.headers.origin;
+    if (origin && allowed.has(origin)) {
+      res.setHeader('Access-Control-Allow-Origin', origin);
+      res.setHeader('Vary', 'Origin');
+      res.setHeader('Access-Control-Allow-Headers', 'Authorization, Content-Type');
+      res.setHeader('Access-Control-Allow-Methods', 'GET, POST, PUT, PATCH, DELETE, OPTIONS');
+      res.setHeader('Access-Control-Max-Age', '600');
+    }
+    if (req.method === 'OPTIONS') {
+      res.sendStatus(204);
+      return;
+    }
+    next();
+  };
+}
+
+const tokensMatch = (provided: string, expected: string) => {
+  const a = Buffer.from(provided);
+  const b = Buffer.from(expected);
+  return a.length === b.length && timingSafeEqual(a, b);
+};
+
+/**
+ * Requires `Authorization: Bearer <token>` when a token is configured. The
+ * desktop host generates one per launch so other local processes and browser
+ * tabs can't read conversations or the stored API keys.
+ */
+export function hostTokenMiddleware(token?: string) {
+  return (req: Request, res: Response, next: NextFunction) => {
+    if (!token) {
+      next();
+      return;
+    }
+    const header = req.headers.authorization ?? '';
+    const provided = header.startsWith('Bearer ') ? header.slice('Bearer '.length) : '';
+    if (!tokensMatch(provided, token)) {
+      res.status(401).json({ error: 'Unauthorized' });
+      return;
+    }
+    next();
+  };
+}
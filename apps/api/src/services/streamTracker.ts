@@ -108,6 +108,14 @@ export class StreamTracker {
     }
   }
 
+  /**
+   * Flush every pending update (used during shutdown so partial content survives)
+   */
+  async flushAll(): Promise<void> {
+    const streamIds = Array.from(this.pendingUpdates.keys());
+    await Promise.all(streamIds.map((streamId) => this.flushUpdate(streamId)));
+  }
+
   /**
    * Mark stream as pending (client disconnected but may resume)
    */
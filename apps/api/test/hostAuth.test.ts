@@ -0,0 +1,75 @@
+import { afterEach, describe, expect, it } from 'vitest';
+import express from 'express';
+import type { AddressInfo } from 'net';
+import type { Server } from 'http';
+import { corsMiddleware, hostTokenMiddleware } from '../src/middleware/hostAuth';
+
+let server: Server | null = null;
+
+const start = async (token?: string) => {
+  const app = express();
+  app.use(corsMiddleware(['http://localhost:5173']));
+  app.get('/api/health', (_req, res) => {
+    res.json({ ok: true });
+  });
+  app.use('/api', hostTokenMiddleware(token));
+  app.get('/api/settings', (_req, res) => {
+    res.json({ ok: true });
+  });
+  server = await new Promise<Server>((resolve) => {
+    const listening = app.listen(0, '127.0.0.1', () => resolve(listening));
+  });
+  const { port } = server.address() as AddressInfo;
+  return (path: string, init?: RequestInit) => fetch(`http://127.0.0.1:${port}${path}`, init);
+};
+
+afterEach(async () => {
+  await new Promise((resolve) => server?.close(resolve) ?? resolve(null));
+  server = null;
+});
+
+describe('hostTokenMiddleware', () => {
+  it('allows every request when no token is configured', async () => {
+    const call = await start();
+    expect((await call('/api/settings')).status).toBe(200);
+  });
+
+  it('rejects requests without the right token', async () => {
+    const call = await start('secret');
+    expect((await call('/api/settings')).status).toBe(401);
+    const wrong = await call('/api/settings', { headers: { Authorization: 'Bearer wrong' } });
+    expect(wrong.status).toBe(401);
+  });
+
+  it('accepts the bearer token and leaves health public', async () => {
+    const call = await start('secret');
+    const res = await call('/api/settings', { headers: { Authorization: 'Bearer secret' } });
+    expect(res.status).toBe(200);
+    expect((await call('/api/health')).status).toBe(200);
+  });
+});
+
+describe('corsMiddleware', () => {
+  it('answers preflights from the desktop webview without a token', async () => {
+    const call = await start('secret');
+    const res = await call('/api/settings', {
+      method: 'OPTIONS',
+      headers: { Origin: 'tauri://localhost' },
+    });
+    expect(res.status).toBe(204);
+    expect(res.headers.get('access-control-allow-origin')).toBe('tauri://localhost');
+    expect(res.headers.get('access-control-allow-headers')).toContain('Authorization');
+  });
+
+  it('allows configured extra origins', async () => {
+    const call = await start();
+    const res = await call('/api/health', { headers: { Origin: 'http://localhost:5173' } });
+    expect(res.headers.get('access-control-allow-origin')).toBe('http://localhost:5173');
+  });
+
+  it('sends no CORS headers to other origins', async () => {
+    const call = await start();
+    const res = await call('/api/health', { headers: { Origin: 'https://evil.example' } });
+    expect(res.headers.get('access-control-allow-origin')).toBeNull();
+  });
+});
@@ -1,3 +1,73 @@
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
 fn main() {
+  write_integrity_manifest();
   tauri_build::build();
 }
+
+/// Records SHA-256 checksums of the bundled Node runtime and the API build,
+/// plus the runtime's major version, so the host can verify them at launch.
+/// `scripts/prepare-tauri.sh` and the API build must have run first.
+fn write_integrity_manifest() {
+  let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR"));
+  let node = manifest_dir.join("bin").join("node");
+  let node_version = manifest_dir.join("bin").join("node.version");
+  let api_dist = manifest_dir.join("..").join("..").join("api").join("dist");
+  for path in [&node, &node_version, &api_dist] {
+    println!("cargo:rerun-if-changed={}", path.display());
+  }
+
+  let node_sha256 = fs::read(&node).ok().map(|bytes| format!("{:x}", Sha256::digest(bytes)));
+  let node_major = fs::read_to_string(&node_version)
+    .ok()
+    .and_then(|version| parse_major(&version));
+  let mut api_files = Vec::new();
+  collect_files(&api_dist, &api_dist, &mut api_files);
+  api_files.sort();
+  if node_sha256.is_none() || api_files.is_empty() {
+    println!(
+      "cargo:warning=bin/node or apps/api/dist is missing; the bundle will not be verifiable"
+    );
+  }
+
+  let mut out = String::new();
+  writeln!(out, "pub const NODE_SHA256: Option<&str> = {node_sha256:?};").unwrap();
+  writeln!(out, "pub const NODE_MAJOR: Option<u32> = {node_major:?};").unwrap();
+  out.push_str("pub const API_FILES: &[(&str, &str)] = &[\n");
+  for (path, hash) in &api_files {
+    writeln!(out, "  ({path:?}, {hash:?}),").unwrap();
+  }
+  out.push_str("];\n");
+  let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR"));
+  fs::write(out_dir.join("integrity.rs"), out).expect("write integrity manifest");
+}
+
+/// Every file under `dir` as (`/`-separated path relative to `root`, sha256).
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(String, String)>) {
+  let Ok(entries) = fs::read_dir(dir) else {
+    return;
+  };
+  for entry in entries.flatten() {
+    let path = entry.path();
+    if path.is_dir() {
+      collect_files(root, &path, files);
+    } else if let Ok(bytes) = fs::read(&path) {
+      let relative = path.strip_prefix(root).expect("path under root");
+      let relative = relative
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+      files.push((relative, format!("{:x}", Sha256::digest(bytes))));
+    }
+  }
+}
+
+fn parse_major(version: &str) -> Option<u32> {
+  version.trim().trim_start_matches('v').split('.').next()?.parse().ok()
+}
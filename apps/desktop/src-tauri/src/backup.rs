@@ -0,0 +1,705 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rusqlite::backup::Backup;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::db;
+use crate::paths;
+use crate::sidecar::{ApiProcess, ApiState};
+
+pub const BACKUP_DIR: &str = "backups";
+pub const ARCHIVE_SUFFIX: &str = ".tar.gz";
+// Archive names end in `-<timestamp>.tar.gz`, local time.
+pub const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const STAMP_LEN: usize = "20260101-000000".len();
+const MANUAL_PREFIX: &str = "pro-chat";
+const MANIFEST_FILE: &str = "manifest.json";
+const FORMAT: u32 = 1;
+// Directories under the data directory that hold user state, next to the database.
+const DATA_DIRS: [&str; 2] = ["storage", "memory"];
+// Restores unpack and retire data here, inside the data directory so the swap
+// is a rename on the same filesystem.
+const STAGING_DIR: &str = "restore-staging";
+const RETIRED_DIR: &str = "restore-previous";
+// What a restore replaced ends up in `backups/<prefix>-<timestamp>/`.
+const PRE_RESTORE_PREFIX: &str = "pro-chat-pre-restore";
+
+// Backups and restores take a while; running two at once would race on the
+// same files.
+static BUSY: Mutex<()> = Mutex::new(());
+
+/// Written into every archive, after the files it describes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+  pub format: u32,
+  pub app_version: String,
+  pub created_at: String,
+  pub files: Vec<ManifestEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEntry {
+  pub path: String,
+  pub size: u64,
+  pub sha256: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+  pub path: PathBuf,
+  pub size: u64,
+  pub modified_ms: u64,
+}
+
+impl BackupInfo {
+  fn read(path: &Path) -> io::Result<Self> {
+    let meta = fs::metadata(path)?;
+    Ok(BackupInfo {
+      path: path.to_path_buf(),
+      size: meta.len(),
+      modified_ms: meta
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as u64),
+    })
+  }
+}
+
+fn lock() -> io::Result<MutexGuard<'static, ()>> {
+  BUSY
+    .try_lock()
+    .map_err(|_| io::Error::other("another backup or restore is already running"))
+}
+
+/// Writes `<prefix>-<timestamp>.tar.gz` into `out_dir`: an online backup of
+/// the database (consistent even while the sidecar writes to it), everything
+/// under `storage/` and `memory/`, and a manifest with checksums. The archive
+/// only appears under its final name once it is complete.
+pub fn create(
+  data_dir: &Path,
+  app_version: &str,
+  out_dir: &Path,
+  prefix: &str,
+) -> io::Result<BackupInfo> {
+  let _busy = lock()?;
+  fs::create_dir_all(out_dir)?;
+  let now = chrono::Local::now();
+  let name = format!("{prefix}-{}{ARCHIVE_SUFFIX}", now.format(TIMESTAMP_FORMAT));
+  let path = out_dir.join(&name);
+  let partial = out_dir.join(format!("{name}.partial"));
+  match write_archive(data_dir, app_version, &now.to_rfc3339(), &partial) {
+    Ok(manifest) => {
+      fs::rename(&partial, &path)?;
+      log::info!(
+        "Backed up {} files to {}",
+        manifest.files.len(),
+        path.display()
+      );
+      BackupInfo::read(&path)
+    }
+    Err(err) => {
+      let _ = fs::remove_file(&partial);
+      Err(err)
+    }
+  }
+}
+
+fn write_archive(
+  data_dir: &Path,
+  app_version: &str,
+  created_at: &str,
+  dest: &Path,
+) -> io::Result<Manifest> {
+  let encoder = GzEncoder::new(File::create(dest)?, Compression::default());
+  let mut archive = tar::Builder::new(encoder);
+  let mut files = Vec::new();
+
+  let database = data_dir.join(db::DB_FILE);
+  if database.exists() {
+    let copy = dest.with_extension("db");
+    let appended = snapshot_database(&database, &copy)
+      .and_then(|()| append_file(&mut archive, &copy, db::DB_FILE));
+    let _ = fs::remove_file(&copy);
+    files.push(appended?);
+  }
+  for dir in DATA_DIRS {
+    for (path, name) in list_files(&data_dir.join(dir), dir)? {
+      files.push(append_file(&mut archive, &path, &name)?);
+    }
+  }
+
+  let manifest = Manifest {
+    format: FORMAT,
+    app_version: app_version.to_string(),
+    created_at: created_at.to_string(),
+    files,
+  };
+  let bytes = serde_json::to_vec_pretty(&manifest)?;
+  let mut header = tar::Header::new_gnu();
+  header.set_size(bytes.len() as u64);
+  header.set_mode(0o644);
+  header.set_mtime(unix_secs(SystemTime::now()));
+  archive.append_data(&mut header, MANIFEST_FILE, bytes.as_slice())?;
+  archive.into_inner()?.finish()?.sync_all()?;
+  Ok(manifest)
+}
+
+/// Copies the live database through SQLite's online backup API, in a single
+/// step so the copy is one consistent read.
+pub fn snapshot_database(source: &Path, dest: &Path) -> io::Result<()> {
+  let _ = fs::remove_file(dest);
+  let source = db::open_read_only(source).map_err(io::Error::other)?;
+  let mut copy = rusqlite::Connection::open(dest).map_err(io::Error::other)?;
+  Backup::new(&source, &mut copy)
+    .and_then(|backup| backup.run_to_completion(-1, Duration::from_millis(50), None))
+    .map_err(io::Error::other)
+}
+
+fn append_file<W: io::Write>(
+  archive: &mut tar::Builder<W>,
+  path: &Path,
+  name: &str,
+) -> io::Result<ManifestEntry> {
+  let file = File::open(path)?;
+  let meta = file.metadata()?;
+  let mut header = tar::Header::new_gnu();
+  header.set_size(meta.len());
+  header.set_mode(0o600);
+  header.set_mtime(meta.modified().map(unix_secs).unwrap_or(0));
+  let mut reader = HashingReader::new(file, meta.len());
+  archive.append_data(&mut header, name, &mut reader)?;
+  Ok(ManifestEntry {
+    path: name.to_string(),
+    size: meta.len(),
+    sha256: reader.digest(),
+  })
+}
+
+/// Regular files under `root`, sorted, with archive names prefixed by `prefix`.
+/// Symlinks are skipped rather than followed out of the data directory.
+fn list_files(root: &Path, prefix: &str) -> io::Result<Vec<(PathBuf, String)>> {
+  let mut files = Vec::new();
+  let entries = match fs::read_dir(root) {
+    Ok(entries) => entries,
+    Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(files),
+    Err(err) => return Err(err),
+  };
+  for entry in entries {
+    let entry = entry?;
+    let file_type = entry.file_type()?;
+    let name = format!("{prefix}/{}", entry.file_name().to_string_lossy());
+    if file_type.is_dir() {
+      files.extend(list_files(&entry.path(), &name)?);
+    } else if file_type.is_file() {
+      files.push((entry.path(), name));
+    }
+  }
+  files.sort_by(|a, b| a.1.cmp(&b.1));
+  Ok(files)
+}
+
+/// Hashes what passes through, and refuses to come up short: tar has already
+/// written the size into the header, so a file that shrank mid-read would
+/// otherwise corrupt the archive.
+struct HashingReader<R> {
+  inner: R,
+  remaining: u64,
+  hasher: Sha256,
+}
+
+impl<R: Read> HashingReader<R> {
+  fn new(inner: R, size: u64) -> Self {
+    HashingReader {
+      inner,
+      remaining: size,
+      hasher: Sha256::new(),
+    }
+  }
+
+  fn digest(self) -> String {
+    format!("{:x}", self.hasher.finalize())
+  }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    if self.remaining == 0 {
+      return Ok(0);
+    }
+    let wanted = self.remaining.min(buf.len() as u64) as usize;
+    let read = self.inner.read(&mut buf[..wanted])?;
+    if read == 0 {
+      return Err(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "a file changed size while it was being read",
+      ));
+    }
+    self.remaining -= read as u64;
+    self.hasher.update(&buf[..read]);
+    Ok(read)
+  }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Unpacks `archive` into `staging` and checks it against its manifest: every
+/// listed file present with the right size and checksum, nothing unlisted,
+/// nothing outside the database and data directories, and a database that
+/// passes `quick_check`.
+fn unpack(archive: &Path, staging: &Path) -> io::Result<Manifest> {
+  let mut tar = tar::Archive::new(GzDecoder::new(File::open(archive)?));
+  let mut manifest: Option<Manifest> = None;
+  let mut found = BTreeMap::new();
+  for entry in tar.entries()? {
+    let mut entry = entry?;
+    let name = entry.path()?.to_string_lossy().into_owned();
+    if !entry.header().entry_type().is_file() {
+      return Err(invalid(format!("unexpected entry {name}")));
+    }
+    if name == MANIFEST_FILE {
+      let mut bytes = Vec::new();
+      entry.read_to_end(&mut bytes)?;
+      manifest = Some(serde_json::from_slice(&bytes).map_err(|err| invalid(err.to_string()))?);
+      continue;
+    }
+    let dest = staged_path(staging, &name)?;
+    if let Some(parent) = dest.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    let size = entry.size();
+    let mut reader = HashingReader::new(&mut entry, size);
+    io::copy(&mut reader, &mut File::create(&dest)?)?;
+    found.insert(name, (size, reader.digest()));
+  }
+
+  let manifest = manifest.ok_or_else(|| invalid("the archive has no manifest"))?;
+  if manifest.format > FORMAT {
+    return Err(invalid(format!(
+      "it was made by a newer pro-chat ({})",
+      manifest.app_version
+    )));
+  }
+  for file in &manifest.files {
+    match found.remove(&file.path) {
+      Some((size, sha256)) if size == file.size && sha256 == file.sha256 => {}
+      Some(_) => return Err(invalid(format!("{} does not match its checksum", file.path))),
+      None => return Err(invalid(format!("{} is missing", file.path))),
+    }
+  }
+  if let Some(extra) = found.keys().next() {
+    return Err(invalid(format!("{extra} is not listed in the manifest")));
+  }
+
+  let database = staging.join(db::DB_FILE);
+  if database.exists() {
+    let problems = db::quick_check(&database).map_err(|err| invalid(err.to_string()))?;
+    if let Some(problem) = problems.first() {
+      return Err(invalid(format!("its database is damaged: {problem}")));
+    }
+  }
+  Ok(manifest)
+}
+
+/// Where an archive member goes under `staging`, refusing anything that is not
+/// the database or inside one of the data directories.
+fn staged_path(staging: &Path, name: &str) -> io::Result<PathBuf> {
+  let path = Path::new(name);
+  let plain = path
+    .components()
+    .all(|component| matches!(component, Component::Normal(_)));
+  let top = path.components().next().and_then(|c| c.as_os_str().to_str());
+  let allowed = match top {
+    Some(db::DB_FILE) => path.components().count() == 1,
+    Some(dir) => DATA_DIRS.contains(&dir) && path.components().count() > 1,
+    None => false,
+  };
+  if plain && allowed {
+    Ok(staging.join(path))
+  } else {
+    Err(invalid(format!("unexpected entry {name}")))
+  }
+}
+
+/// Everything a restore replaces. The journal files are retired with the old
+/// database so SQLite doesn't replay them into the restored one.
+fn replaced_names() -> Vec<String> {
+  let mut names = db::database_files();
+  names.extend(DATA_DIRS.iter().map(|dir| dir.to_string()));
+  names
+}
+
+/// Moves the current data into `retired` and the staged data into place. If
+/// any rename fails, what was already moved is put back.
+fn swap_in(data_dir: &Path, staging: &Path, retired: &Path) -> io::Result<()> {
+  fs::create_dir_all(retired)?;
+  let mut moved: Vec<String> = Vec::new();
+  let mut placed: Vec<String> = Vec::new();
+  let undo = |moved: &[String], placed: &[String]| {
+    for name in placed {
+      let _ = fs::rename(data_dir.join(name), staging.join(name));
+    }
+    for name in moved {
+      let _ = fs::rename(retired.join(name), data_dir.join(name));
+    }
+  };
+
+  for name in replaced_names() {
+    let current = data_dir.join(&name);
+    if fs::symlink_metadata(&current).is_err() {
+      continue;
+    }
+    if let Err(err) = fs::rename(&current, retired.join(&name)) {
+      undo(&moved, &placed);
+      return Err(err);
+    }
+    moved.push(name);
+  }
+  for name in replaced_names() {
+    let staged = staging.join(&name);
+    if !staged.exists() {
+      continue;
+    }
+    if let Err(err) = fs::rename(&staged, data_dir.join(&name)) {
+      undo(&moved, &placed);
+      return Err(err);
+    }
+    placed.push(name);
+  }
+  Ok(())
+}
+
+fn reset_dir(path: &Path) -> io::Result<()> {
+  match fs::remove_dir_all(path) {
+    Ok(()) => {}
+    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+    Err(err) => return Err(err),
+  }
+  fs::create_dir_all(path)
+}
+
+/// Validates `archive`, stops the sidecar, swaps the archived data in and
+/// starts the sidecar again (unless it was stopped to begin with). The data it
+/// replaced is kept under `backups/`, so restoring the wrong archive can be
+/// undone.
+pub fn restore(app: &AppHandle, data_dir: &Path, archive: &Path) -> io::Result<Manifest> {
+  restore_with(data_dir, archive, app.try_state::<ApiProcess>().as_deref())
+}
+
+fn restore_with(
+  data_dir: &Path,
+  archive: &Path,
+  process: Option<&ApiProcess>,
+) -> io::Result<Manifest> {
+  let _busy = lock()?;
+  let staging = data_dir.join(STAGING_DIR);
+  let retired = data_dir.join(RETIRED_DIR);
+  reset_dir(&staging)?;
+  let manifest = match unpack(archive, &staging) {
+    Ok(manifest) => manifest,
+    Err(err) => {
+      let _ = fs::remove_dir_all(&staging);
+      return Err(io::Error::new(
+        err.kind(),
+        format!("{} is not a usable backup: {err}", archive.display()),
+      ));
+    }
+  };
+  reset_dir(&retired)?;
+
+  let was_running = process.is_some_and(|process| process.status().state != ApiState::Stopped);
+  if let Some(process) = process {
+    process.stop();
+  }
+  let swapped = swap_in(data_dir, &staging, &retired);
+  let _ = fs::remove_dir_all(&staging);
+  if swapped.is_ok() {
+    match keep_replaced(data_dir, &retired) {
+      Ok(Some(kept)) => log::info!("Moved the replaced data to {}", kept.display()),
+      Ok(None) => {}
+      Err(err) => log::warn!("Failed to keep the replaced data: {err}"),
+    }
+    log::info!(
+      "Restored {} files from {} (pro-chat {}, {})",
+      manifest.files.len(),
+      archive.display(),
+      manifest.app_version,
+      manifest.created_at
+    );
+  }
+  if let Some(process) = process.filter(|_| was_running) {
+    if let Err(err) = process.start() {
+      log::error!("Failed to restart the API server after the restore: {err}");
+    }
+  }
+  swapped.map(|()| manifest)
+}
+
+/// Moves what a restore replaced from `retired` into a new
+/// `backups/pro-chat-pre-restore-<timestamp>` directory, unless nothing was
+/// replaced.
+fn keep_replaced(data_dir: &Path, retired: &Path) -> io::Result<Option<PathBuf>> {
+  if fs::read_dir(retired)?.next().is_none() {
+    fs::remove_dir(retired)?;
+    return Ok(None);
+  }
+  let backups = data_dir.join(BACKUP_DIR);
+  fs::create_dir_all(&backups)?;
+  let stamp = chrono::Local::now().format(TIMESTAMP_FORMAT);
+  let kept = backups.join(format!("{PRE_RESTORE_PREFIX}-{stamp}"));
+  fs::rename(retired, &kept)?;
+  Ok(Some(kept))
+}
+
+/// When an archive named `<prefix>-<timestamp>.tar.gz` was taken.
+pub fn archive_time(name: &str) -> Option<chrono::NaiveDateTime> {
+  stamp_time(name.strip_suffix(ARCHIVE_SUFFIX)?)
+}
+
+/// The time in a file name stem that ends in `-<timestamp>`.
+pub fn stamp_time(stem: &str) -> Option<chrono::NaiveDateTime> {
+  // The timestamp itself contains a dash, so it is split off by length.
+  let stamp = stem.get(stem.len().checked_sub(STAMP_LEN)?..)?;
+  chrono::NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT).ok()
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+  time
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or(0)
+}
+
+fn backup_dir(app: &AppHandle) -> Result<PathBuf, String> {
+  paths::app_data_dir(app)
+    .map(|dir| dir.join(BACKUP_DIR))
+    .map_err(|err| err.to_string())
+}
+
+/// Backups in the app's `backups` directory, newest first.
+#[tauri::command]
+pub fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, String> {
+  let dir = backup_dir(&app)?;
+  let entries = match fs::read_dir(&dir) {
+    Ok(entries) => entries,
+    Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+    Err(err) => return Err(err.to_string()),
+  };
+  let mut backups: Vec<BackupInfo> = entries
+    .flatten()
+    .map(|entry| entry.path())
+    .filter(|path| path.to_string_lossy().ends_with(ARCHIVE_SUFFIX))
+    .filter_map(|path| BackupInfo::read(&path).ok())
+    .collect();
+  backups.sort_by(|a, b| b.modified_ms.cmp(&a.modified_ms));
+  Ok(backups)
+}
+
+/// Writes a backup into `directory`, or the app's `backups` directory.
+#[tauri::command]
+pub async fn create_backup(
+  app: AppHandle,
+  directory: Option<PathBuf>,
+) -> Result<BackupInfo, String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    let data_dir = paths::app_data_dir(&app).map_err(|err| err.to_string())?;
+    let out_dir = directory.unwrap_or_else(|| data_dir.join(BACKUP_DIR));
+    let version = app.package_info().version.to_string();
+    create(&data_dir, &version, &out_dir, MANUAL_PREFIX)
+      .map_err(|err| format!("Backup failed: {err}"))
+  })
+  .await
+  .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn restore_backup(app: AppHandle, path: PathBuf) -> Result<Manifest, String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    let data_dir = paths::app_data_dir(&app).map_err(|err| err.to_string())?;
+    restore(&app, &data_dir, &path).map_err(|err| format!("Restore failed: {err}"))
+  })
+  .await
+  .map_err(|err| err.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::TempDir;
+
+  /// A gzipped tar of `members`, names written as given so that archives no
+  /// well-behaved writer would produce can be built too.
+  fn raw_archive(path: &Path, members: &[(&str, &[u8])]) {
+    let file = File::create(path).unwrap();
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    for (name, data) in members {
+      let mut header = tar::Header::new_gnu();
+      header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name.as_bytes());
+      header.set_entry_type(tar::EntryType::Regular);
+      header.set_size(data.len() as u64);
+      header.set_mode(0o600);
+      header.set_cksum();
+      builder.append(&header, *data).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+  }
+
+  fn manifest(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let manifest = Manifest {
+      format: FORMAT,
+      app_version: "1.0.0".to_string(),
+      created_at: "2026-01-01T00:00:00+00:00".to_string(),
+      files: files
+        .iter()
+        .map(|(path, data)| ManifestEntry {
+          path: path.to_string(),
+          size: data.len() as u64,
+          sha256: format!("{:x}", Sha256::digest(data)),
+        })
+        .collect(),
+    };
+    serde_json::to_vec(&manifest).unwrap()
+  }
+
+  fn unpack_error(members: &[(&str, &[u8])]) -> String {
+    let tmp = TempDir::new();
+    let archive = tmp.path().join("test.tar.gz");
+    raw_archive(&archive, members);
+    let staging = tmp.path().join("staging");
+    fs::create_dir_all(&staging).unwrap();
+    unpack(&archive, &staging).unwrap_err().to_string()
+  }
+
+  #[test]
+  fn staged_path_takes_the_database_and_data_directories() {
+    let staging = Path::new("staging");
+    for name in [db::DB_FILE, "storage/a/b.png", "memory/notes.md"] {
+      assert_eq!(staged_path(staging, name).unwrap(), staging.join(name));
+    }
+  }
+
+  #[test]
+  fn staged_path_rejects_everything_else() {
+    for name in [
+      "../x",
+      "/etc/passwd",
+      "storage/../../x",
+      "./pro-chat.db",
+      "storage",
+      "pro-chat.db/x",
+      "pro-chat.db-wal",
+      "other/file",
+      "",
+    ] {
+      assert!(staged_path(Path::new("staging"), name).is_err(), "{name}");
+    }
+  }
+
+  #[test]
+  fn unpack_refuses_to_write_outside_staging() {
+    let data: &[u8] = b"nope";
+    let listing = manifest(&[("../escaped", data)]);
+    let tmp = TempDir::new();
+    let archive = tmp.path().join("test.tar.gz");
+    raw_archive(&archive, &[("../escaped", data), (MANIFEST_FILE, &listing)]);
+    let staging = tmp.path().join("staging");
+    fs::create_dir_all(&staging).unwrap();
+
+    let err = unpack(&archive, &staging).unwrap_err();
+    assert!(err.to_string().contains("unexpected entry ../escaped"), "{err}");
+    assert!(!tmp.path().join("escaped").exists());
+  }
+
+  #[test]
+  fn unpack_needs_a_manifest() {
+    let err = unpack_error(&[("memory/notes.md", b"hello")]);
+    assert!(err.contains("no manifest"), "{err}");
+  }
+
+  #[test]
+  fn unpack_rejects_members_the_manifest_does_not_list() {
+    let listing = manifest(&[("memory/notes.md", b"hello")]);
+    let err = unpack_error(&[
+      ("memory/notes.md", b"hello"),
+      ("storage/extra.png", b"extra"),
+      (MANIFEST_FILE, &listing),
+    ]);
+    assert!(err.contains("storage/extra.png is not listed"), "{err}");
+  }
+
+  #[test]
+  fn unpack_rejects_a_checksum_mismatch() {
+    let listing = manifest(&[("memory/notes.md", b"hello")]);
+    let err = unpack_error(&[("memory/notes.md", b"jello"), (MANIFEST_FILE, &listing)]);
+    assert!(err.contains("memory/notes.md does not match its checksum"), "{err}");
+
+    let listing = manifest(&[("memory/notes.md", b"hello"), ("memory/gone.md", b"")]);
+    let err = unpack_error(&[("memory/notes.md", b"hello"), (MANIFEST_FILE, &listing)]);
+    assert!(err.contains("memory/gone.md is missing"), "{err}");
+  }
+
+  #[test]
+  fn backup_and_restore_round_trip() {
+    let tmp = TempDir::new();
+    let source = tmp.path().join("source");
+    fs::create_dir_all(source.join("storage/img")).unwrap();
+    fs::create_dir_all(source.join("memory")).unwrap();
+    fs::write(source.join("storage/img/a.png"), b"png bytes").unwrap();
+    fs::write(source.join("memory/notes.md"), b"remember this").unwrap();
+    let conn = rusqlite::Connection::open(source.join(db::DB_FILE)).unwrap();
+    conn
+      .execute_batch("CREATE TABLE note (body TEXT); INSERT INTO note VALUES ('kept');")
+      .unwrap();
+    drop(conn);
+
+    let out = tmp.path().join("out");
+    let info = create(&source, "1.2.3", &out, MANUAL_PREFIX).unwrap();
+    let name = info.path.file_name().unwrap().to_str().unwrap();
+    assert!(name.starts_with("pro-chat-"), "{name}");
+    assert!(archive_time(name).is_some(), "{name}");
+    assert_eq!(fs::read_dir(&out).unwrap().count(), 1, "no partial file is left behind");
+
+    let target = tmp.path().join("target");
+    fs::create_dir_all(target.join("storage")).unwrap();
+    fs::write(target.join("storage/old.png"), b"old").unwrap();
+    fs::write(target.join(db::DB_FILE), b"not the restored database").unwrap();
+    let manifest = restore_with(&target, &info.path, None).unwrap();
+
+    assert_eq!(manifest.app_version, "1.2.3");
+    let paths: Vec<&str> = manifest.files.iter().map(|file| file.path.as_str()).collect();
+    assert_eq!(paths, [db::DB_FILE, "storage/img/a.png", "memory/notes.md"]);
+    assert_eq!(fs::read(target.join("storage/img/a.png")).unwrap(), b"png bytes");
+    assert_eq!(fs::read(target.join("memory/notes.md")).unwrap(), b"remember this");
+    assert!(!target.join("storage/old.png").exists());
+    assert!(!target.join(STAGING_DIR).exists() && !target.join(RETIRED_DIR).exists());
+    // The replaced data is kept for undoing the restore.
+    let kept: Vec<PathBuf> = fs::read_dir(target.join(BACKUP_DIR))
+      .unwrap()
+      .map(|entry| entry.unwrap().path())
+      .collect();
+    assert_eq!(kept.len(), 1);
+    assert!(kept[0].file_name().unwrap().to_str().unwrap().starts_with(PRE_RESTORE_PREFIX));
+    assert_eq!(fs::read(kept[0].join("storage/old.png")).unwrap(), b"old");
+    assert_eq!(fs::read(kept[0].join(db::DB_FILE)).unwrap(), b"not the restored database");
+    let body: String = rusqlite::Connection::open(target.join(db::DB_FILE))
+      .unwrap()
+      .query_row("SELECT body FROM note", [], |row| row.get(0))
+      .unwrap();
+    assert_eq!(body, "kept");
+  }
+}
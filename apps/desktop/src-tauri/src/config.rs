@@ -0,0 +1,456 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::SidecarError;
+use crate::paths;
+use crate::sidecar::{ApiProcess, ApiState};
+
+pub const CONFIG_FILE: &str = "config.toml";
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `config.toml` in the app data directory. Every field is optional; anything
+/// left out keeps the API's own default.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HostConfig {
+  pub web_fetch: WebFetchConfig,
+  pub trace: TraceConfig,
+  pub openrouter: OpenRouterConfig,
+  pub resources: ResourceConfig,
+  pub database: DatabaseConfig,
+  pub watchdog: WatchdogConfig,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebFetchConfig {
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub allow_domains: Vec<String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub deny_domains: Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_redirects: Option<u32>,
+  pub render: RenderConfig,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderMode {
+  Off,
+  Auto,
+  Always,
+}
+
+impl RenderMode {
+  fn as_str(self) -> &'static str {
+    match self {
+      RenderMode::Off => "off",
+      RenderMode::Auto => "auto",
+      RenderMode::Always => "always",
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RenderConfig {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub mode: Option<RenderMode>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub url: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub header: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub token: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TraceConfig {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_events: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_chars: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_sources: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_source_chars: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_source_snippet_chars: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub retention_days: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OpenRouterConfig {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub app_url: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub app_name: Option<String>,
+}
+
+/// Limits the host puts on the sidecar rather than settings for the API
+/// itself. The ceilings and nice level are applied at spawn on Linux.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResourceConfig {
+  /// `RLIMIT_AS`. Node reserves several GB of address space up front, so
+  /// this has to be generous; `max_data_mb` is usually the better ceiling.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_address_space_mb: Option<u64>,
+  /// `RLIMIT_DATA`: heap and other private memory.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_data_mb: Option<u64>,
+  /// `RLIMIT_NOFILE`.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_open_files: Option<u64>,
+  /// 0 (normal) to 19 (lowest priority).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub nice: Option<i32>,
+  /// Resident memory of the sidecar's process group above which it is
+  /// restarted, once no stream is in progress.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub soft_rss_mb: Option<u64>,
+}
+
+/// Checks the host runs on the database before the sidecar starts.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+  /// Use `PRAGMA integrity_check` rather than the faster `quick_check`.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub full_integrity_check: Option<bool>,
+}
+
+/// How the host health-checks a running sidecar and when it gives up on it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatchdogConfig {
+  /// Seconds between `/api/health` probes.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub interval_secs: Option<u64>,
+  /// Consecutive failed probes before the sidecar is restarted.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub failure_threshold: Option<u32>,
+  /// Longest a restart may wait on in-flight streams before it goes ahead.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_deferral_secs: Option<u64>,
+}
+
+impl HostConfig {
+  /// Rejects values the API would choke on, naming the offending key.
+  pub fn validate(&self) -> Result<(), String> {
+    let web_fetch = &self.web_fetch;
+    for (key, domains) in [
+      ("web_fetch.allow_domains", &web_fetch.allow_domains),
+      ("web_fetch.deny_domains", &web_fetch.deny_domains),
+    ] {
+      for domain in domains {
+        if domain.is_empty() || domain.contains(|c: char| c.is_whitespace() || c == ',') {
+          return Err(format!("{key}: \"{domain}\" is not a domain name"));
+        }
+      }
+    }
+    if web_fetch.max_redirects.is_some_and(|max| max > 20) {
+      return Err("web_fetch.max_redirects must be between 0 and 20".to_string());
+    }
+    let render = &web_fetch.render;
+    if let Some(url) = &render.url {
+      if http_url(url).is_none() {
+        return Err("web_fetch.render.url must be an http:// or https:// URL".to_string());
+      }
+    }
+    if matches!(render.mode, Some(RenderMode::Auto | RenderMode::Always)) && render.url.is_none() {
+      return Err("web_fetch.render.url is required when web_fetch.render.mode is set".to_string());
+    }
+
+    let trace = &self.trace;
+    for (key, value) in [
+      ("trace.max_events", trace.max_events),
+      ("trace.max_chars", trace.max_chars),
+      ("trace.max_sources", trace.max_sources),
+      ("trace.max_source_chars", trace.max_source_chars),
+      ("trace.max_source_snippet_chars", trace.max_source_snippet_chars),
+      ("trace.retention_days", trace.retention_days),
+    ] {
+      if value == Some(0) {
+        return Err(format!("{key} must be at least 1"));
+      }
+    }
+
+    if let Some(url) = &self.openrouter.app_url {
+      if http_url(url).is_none() {
+        return Err("openrouter.app_url must be an http:// or https:// URL".to_string());
+      }
+    }
+    if self.openrouter.app_name.as_deref().is_some_and(|name| name.trim().is_empty()) {
+      return Err("openrouter.app_name must not be empty".to_string());
+    }
+
+    let resources = &self.resources;
+    for (key, value, min) in [
+      ("resources.max_address_space_mb", resources.max_address_space_mb, 1024),
+      ("resources.max_data_mb", resources.max_data_mb, 512),
+      ("resources.max_open_files", resources.max_open_files, 256),
+      ("resources.soft_rss_mb", resources.soft_rss_mb, 256),
+    ] {
+      if value.is_some_and(|value| value < min) {
+        return Err(format!("{key} must be at least {min}"));
+      }
+    }
+    if resources.nice.is_some_and(|nice| !(0..=19).contains(&nice)) {
+      return Err("resources.nice must be between 0 and 19".to_string());
+    }
+
+    let watchdog = &self.watchdog;
+    if watchdog.interval_secs.is_some_and(|secs| secs == 0) {
+      return Err("watchdog.interval_secs must be at least 1".to_string());
+    }
+    if watchdog.failure_threshold.is_some_and(|failures| failures == 0) {
+      return Err("watchdog.failure_threshold must be at least 1".to_string());
+    }
+    Ok(())
+  }
+
+  /// The variables `apps/api/src/env.ts` reads, for whatever is set.
+  pub fn to_env(&self) -> Vec<(&'static str, String)> {
+    let web_fetch = &self.web_fetch;
+    let render = &web_fetch.render;
+    let trace = &self.trace;
+    let mut vars = Vec::new();
+    if !web_fetch.allow_domains.is_empty() {
+      vars.push(("WEB_FETCH_ALLOW_DOMAINS", web_fetch.allow_domains.join(",")));
+    }
+    if !web_fetch.deny_domains.is_empty() {
+      vars.push(("WEB_FETCH_DENY_DOMAINS", web_fetch.deny_domains.join(",")));
+    }
+    let optional = [
+      ("WEB_FETCH_MAX_REDIRECTS", web_fetch.max_redirects.map(|v| v.to_string())),
+      ("WEB_FETCH_RENDER_MODE", render.mode.map(|mode| mode.as_str().to_string())),
+      ("WEB_FETCH_RENDER_URL", render.url.clone()),
+      ("WEB_FETCH_RENDER_HEADER", render.header.clone()),
+      ("WEB_FETCH_RENDER_TOKEN", render.token.clone()),
+      ("TRACE_MAX_EVENTS", trace.max_events.map(|v| v.to_string())),
+      ("TRACE_MAX_CHARS", trace.max_chars.map(|v| v.to_string())),
+      ("TRACE_MAX_SOURCES", trace.max_sources.map(|v| v.to_string())),
+      ("TRACE_MAX_SOURCE_CHARS", trace.max_source_chars.map(|v| v.to_string())),
+      (
+        "TRACE_MAX_SOURCE_SNIPPET_CHARS",
+        trace.max_source_snippet_chars.map(|v| v.to_string()),
+      ),
+      ("TRACE_RETENTION_DAYS", trace.retention_days.map(|v| v.to_string())),
+      ("OPENROUTER_APP_URL", self.openrouter.app_url.clone()),
+      ("OPENROUTER_APP_NAME", self.openrouter.app_name.clone()),
+    ];
+    vars.extend(optional.into_iter().filter_map(|(name, value)| Some((name, value?))));
+    vars
+  }
+}
+
+pub const URL_ERROR: &str = "The URL must start with http:// or https://.";
+
+/// `url` without surrounding whitespace or a trailing `/`, if it is an http://
+/// or https:// URL.
+pub fn http_url(url: &str) -> Option<String> {
+  let url = url.trim().trim_end_matches('/');
+  (url.starts_with("http://") || url.starts_with("https://")).then(|| url.to_string())
+}
+
+fn config_path(data_dir: &Path) -> PathBuf {
+  data_dir.join(CONFIG_FILE)
+}
+
+/// Reads and validates `config.toml`; a missing file is an empty config.
+pub fn load(data_dir: &Path) -> Result<HostConfig, SidecarError> {
+  let path = config_path(data_dir);
+  let contents = match fs::read_to_string(&path) {
+    Ok(contents) => contents,
+    Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(HostConfig::default()),
+    Err(err) => {
+      return Err(SidecarError::Config {
+        path,
+        message: err.to_string(),
+      })
+    }
+  };
+  let config: HostConfig = toml::from_str(&contents).map_err(|err| SidecarError::Config {
+    path: path.clone(),
+    message: err.to_string(),
+  })?;
+  config
+    .validate()
+    .map_err(|message| SidecarError::Config { path, message })?;
+  Ok(config)
+}
+
+/// Writes via a temporary file so the watcher never sees half a config.
+/// Comments in a hand-edited file are not preserved.
+fn save(data_dir: &Path, config: &HostConfig) -> io::Result<()> {
+  let contents = toml::to_string_pretty(config).map_err(io::Error::other)?;
+  paths::write_private(&config_path(data_dir), contents.as_bytes())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigView {
+  path: PathBuf,
+  config: HostConfig,
+}
+
+#[tauri::command]
+pub fn host_config(app: AppHandle) -> Result<ConfigView, String> {
+  let data_dir = paths::app_data_dir(&app).map_err(|err| err.to_string())?;
+  let config = load(&data_dir).map_err(|err| err.to_string())?;
+  Ok(ConfigView {
+    path: config_path(&data_dir),
+    config,
+  })
+}
+
+/// Validates and saves `config`. The watcher then restarts the sidecar with it.
+#[tauri::command]
+pub fn update_host_config(app: AppHandle, config: HostConfig) -> Result<(), String> {
+  config.validate()?;
+  let data_dir = paths::app_data_dir(&app).map_err(|err| err.to_string())?;
+  save(&data_dir, &config).map_err(|err| err.to_string())
+}
+
+/// Polls `config.toml` and restarts the sidecar when it changes, so edits from
+/// the settings screen and by hand both apply without relaunching. An invalid
+/// file is logged and the running sidecar is left alone.
+pub fn watch(app: AppHandle) {
+  thread::spawn(move || {
+    let Ok(data_dir) = paths::app_data_dir(&app) else {
+      return;
+    };
+    let path = config_path(&data_dir);
+    let mut last_modified = modified(&path);
+    let mut last_config = load(&data_dir).ok();
+    loop {
+      thread::sleep(WATCH_INTERVAL);
+      let current = modified(&path);
+      if current == last_modified {
+        continue;
+      }
+      last_modified = current;
+      let config = match load(&data_dir) {
+        Ok(config) => config,
+        Err(err) => {
+          log::error!("Ignoring the configuration change: {err}");
+          continue;
+        }
+      };
+      if last_config.as_ref() == Some(&config) {
+        continue;
+      }
+      last_config = Some(config);
+      let Some(process) = app.try_state::<ApiProcess>() else {
+        continue;
+      };
+      // Stopped covers remote and hand-run backends as well as a user stop.
+      if process.status().state == ApiState::Stopped {
+        continue;
+      }
+      log::info!("Configuration changed; restarting the API");
+      if let Err(err) = process.start() {
+        log::error!("Failed to restart the API with the new configuration: {err}");
+      }
+    }
+  });
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+  fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(contents: &str) -> HostConfig {
+    toml::from_str(contents).expect("valid TOML")
+  }
+
+  #[test]
+  fn empty_config_is_valid_and_sets_nothing() {
+    let config = parse("");
+    assert_eq!(config, HostConfig::default());
+    assert_eq!(config.validate(), Ok(()));
+    assert!(config.to_env().is_empty());
+  }
+
+  #[test]
+  fn unknown_keys_are_rejected() {
+    assert!(toml::from_str::<HostConfig>("[trace]\nmax_event = 3\n").is_err());
+  }
+
+  #[test]
+  fn validate_names_the_offending_key() {
+    let cases = [
+      ("[web_fetch]\nallow_domains = [\"a.com, b.com\"]\n", "web_fetch.allow_domains"),
+      ("[web_fetch]\ndeny_domains = [\"\"]\n", "web_fetch.deny_domains"),
+      ("[web_fetch]\nmax_redirects = 21\n", "web_fetch.max_redirects"),
+      ("[web_fetch.render]\nmode = \"auto\"\n", "web_fetch.render.url"),
+      ("[web_fetch.render]\nurl = \"render.local\"\n", "web_fetch.render.url"),
+      ("[trace]\nretention_days = 0\n", "trace.retention_days"),
+      ("[openrouter]\napp_url = \"ftp://example.com\"\n", "openrouter.app_url"),
+      ("[openrouter]\napp_name = \"  \"\n", "openrouter.app_name"),
+      ("[resources]\nsoft_rss_mb = 100\n", "resources.soft_rss_mb"),
+      ("[resources]\nnice = 20\n", "resources.nice"),
+      ("[watchdog]\ninterval_secs = 0\n", "watchdog.interval_secs"),
+      ("[watchdog]\nfailure_threshold = 0\n", "watchdog.failure_threshold"),
+    ];
+    for (contents, key) in cases {
+      let err = parse(contents).validate().expect_err(contents);
+      assert!(err.starts_with(key), "{contents:?} gave {err:?}");
+    }
+  }
+
+  #[test]
+  fn to_env_maps_what_is_set() {
+    let config = parse(
+      r#"
+        [web_fetch]
+        allow_domains = ["example.com", "docs.rs"]
+        max_redirects = 3
+
+        [web_fetch.render]
+        mode = "always"
+        url = "https://render.local"
+
+        [trace]
+        retention_days = 7
+
+        [openrouter]
+        app_name = "pro-chat"
+
+        [resources]
+        soft_rss_mb = 1024
+
+        [watchdog]
+        interval_secs = 30
+        max_deferral_secs = 0
+      "#,
+    );
+    assert_eq!(config.validate(), Ok(()));
+    let env = config.to_env();
+    assert_eq!(
+      env,
+      vec![
+        ("WEB_FETCH_ALLOW_DOMAINS", "example.com,docs.rs".to_string()),
+        ("WEB_FETCH_MAX_REDIRECTS", "3".to_string()),
+        ("WEB_FETCH_RENDER_MODE", "always".to_string()),
+        ("WEB_FETCH_RENDER_URL", "https://render.local".to_string()),
+        ("TRACE_RETENTION_DAYS", "7".to_string()),
+        ("OPENROUTER_APP_NAME", "pro-chat".to_string()),
+      ]
+    );
+  }
+}
@@ -0,0 +1,58 @@
+use std::path::Path;
+use std::time::Duration;
+
+use rusqlite::{Connection, OpenFlags};
+
+pub const DB_FILE: &str = "pro-chat.db";
+/// Files SQLite keeps next to the database, named by appending these.
+pub const JOURNAL_SUFFIXES: [&str; 3] = ["-wal", "-shm", "-journal"];
+
+/// `DB_FILE` and its journal file names, which always move together.
+pub fn database_files() -> Vec<String> {
+  std::iter::once(DB_FILE.to_string())
+    .chain(JOURNAL_SUFFIXES.iter().map(|suffix| format!("{DB_FILE}{suffix}")))
+    .collect()
+}
+
+/// Opens the app database read-only without creating it, with a short busy
+/// timeout so the host never blocks on the sidecar's writes for long.
+pub fn open_read_only(path: &Path) -> rusqlite::Result<Connection> {
+  let conn = Connection::open_with_flags(
+    path,
+    OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+  )?;
+  conn.busy_timeout(Duration::from_secs(2))?;
+  Ok(conn)
+}
+
+/// Streams still marked active or pending that made progress at or after
+/// `since_ms` (Unix milliseconds, which is how Prisma stores SQLite dates).
+pub fn live_stream_count(path: &Path, since_ms: i64) -> rusqlite::Result<u32> {
+  let conn = open_read_only(path)?;
+  conn.query_row(
+    "SELECT COUNT(*) FROM ActiveStream \
+     WHERE status IN ('active', 'pending') AND lastActivityAt >= ?1",
+    [since_ms],
+    |row| row.get(0),
+  )
+}
+
+/// Runs `PRAGMA quick_check` and returns the problems it reports; an empty
+/// list means the database is sound.
+pub fn quick_check(path: &Path) -> rusqlite::Result<Vec<String>> {
+  check(path, "quick_check")
+}
+
+/// Like `quick_check`, but also verifies that every index matches its table,
+/// which takes several times longer on a large database.
+pub fn integrity_check(path: &Path) -> rusqlite::Result<Vec<String>> {
+  check(path, "integrity_check")
+}
+
+fn check(path: &Path, pragma: &str) -> rusqlite::Result<Vec<String>> {
+  let conn = open_read_only(path)?;
+  let mut stmt = conn.prepare(&format!("PRAGMA {pragma}"))?;
+  let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+  let results = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(results.into_iter().filter(|line| line != "ok").collect())
+}
@@ -0,0 +1,53 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::sidecar::{ApiProcess, ApiState};
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(1);
+// Editors often write a file in several steps; wait for the tree to settle.
+const SETTLE_DELAY: Duration = Duration::from_millis(400);
+
+/// Restarts the source-mode sidecar whenever a file under `src_dir` changes,
+/// unless it is stopped.
+/// Polls modification times so no platform file-watching API is needed.
+pub fn watch_api_source(process: ApiProcess, src_dir: PathBuf) {
+  thread::spawn(move || {
+    let mut last = latest_mtime(&src_dir);
+    loop {
+      thread::sleep(SCAN_INTERVAL);
+      let current = latest_mtime(&src_dir);
+      if current <= last {
+        continue;
+      }
+      thread::sleep(SETTLE_DELAY);
+      last = latest_mtime(&src_dir);
+      // Leave a sidecar the user stopped, or that never started, alone.
+      if process.status().state == ApiState::Stopped {
+        continue;
+      }
+      log::info!("API source changed; restarting the sidecar");
+      if let Err(err) = process.start() {
+        log::error!("Failed to restart the API after a source change: {err}");
+      }
+    }
+  });
+}
+
+fn latest_mtime(dir: &Path) -> Option<SystemTime> {
+  let mut latest = None;
+  let Ok(entries) = fs::read_dir(dir) else {
+    return latest;
+  };
+  for entry in entries.flatten() {
+    let path = entry.path();
+    let modified = if path.is_dir() {
+      latest_mtime(&path)
+    } else {
+      entry.metadata().and_then(|meta| meta.modified()).ok()
+    };
+    latest = latest.max(modified);
+  }
+  latest
+}
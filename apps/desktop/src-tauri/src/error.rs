@@ -0,0 +1,210 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Why the API sidecar could not be brought up. Each variant maps to a
+/// message and a hint the startup error screen shows to the user.
+#[derive(Debug)]
+pub enum SidecarError {
+  ResourceDirUnavailable(tauri::Error),
+  DataDirUnavailable(tauri::Error),
+  DataDirUnwritable { path: PathBuf, source: io::Error },
+  MissingEntry { path: PathBuf },
+  NodeNotFound { command: PathBuf },
+  NodeIncompatible { command: PathBuf, found: String, expected: String },
+  Integrity { path: PathBuf, reason: String },
+  Config { path: PathBuf, message: String },
+  PortUnavailable(io::Error),
+  Spawn(io::Error),
+  HealthTimeout { seconds: u64 },
+  CrashLoop { exit_code: Option<i32> },
+  Migration { database: PathBuf, name: Option<String>, reason: String },
+  DatabaseCorrupt { path: PathBuf, problems: Vec<String> },
+  RemoteUnreachable { url: String, reason: String },
+  UpgradeFailed {
+    from: String,
+    to: String,
+    reason: String,
+    database: PathBuf,
+    backup: PathBuf,
+    restored: bool,
+  },
+}
+
+impl SidecarError {
+  /// Stable identifier for the webview, e.g. `missingEntry`.
+  pub fn kind(&self) -> &'static str {
+    match self {
+      SidecarError::ResourceDirUnavailable(_) => "resourceDirUnavailable",
+      SidecarError::DataDirUnavailable(_) => "dataDirUnavailable",
+      SidecarError::DataDirUnwritable { .. } => "dataDirUnwritable",
+      SidecarError::MissingEntry { .. } => "missingEntry",
+      SidecarError::NodeNotFound { .. } => "nodeNotFound",
+      SidecarError::NodeIncompatible { .. } => "nodeIncompatible",
+      SidecarError::Integrity { .. } => "integrity",
+      SidecarError::Config { .. } => "config",
+      SidecarError::PortUnavailable(_) => "portUnavailable",
+      SidecarError::Spawn(_) => "spawn",
+      SidecarError::HealthTimeout { .. } => "healthTimeout",
+      SidecarError::CrashLoop { .. } => "crashLoop",
+      SidecarError::Migration { .. } => "migration",
+      SidecarError::DatabaseCorrupt { .. } => "databaseCorrupt",
+      SidecarError::RemoteUnreachable { .. } => "remoteUnreachable",
+      SidecarError::UpgradeFailed { .. } => "upgradeFailed",
+    }
+  }
+
+  /// What the user can do about it.
+  pub fn hint(&self) -> String {
+    match self {
+      SidecarError::ResourceDirUnavailable(_) | SidecarError::MissingEntry { .. } => {
+        "The app bundle looks incomplete. Reinstall pro-chat.".to_string()
+      }
+      SidecarError::DataDirUnavailable(_) => {
+        "Your user profile has no application data directory. Check your account setup.".to_string()
+      }
+      SidecarError::DataDirUnwritable { path, .. } => format!(
+        "Make sure {} is writable and the disk is not full.",
+        path.display()
+      ),
+      SidecarError::NodeNotFound { .. } => {
+        "Reinstall pro-chat, or install Node.js and make sure `node` is on your PATH.".to_string()
+      }
+      SidecarError::NodeIncompatible { expected, .. } => format!(
+        "Reinstall pro-chat to restore its bundled runtime, or install Node.js {expected}."
+      ),
+      SidecarError::Integrity { .. } => {
+        "Files in the app bundle were changed after it was built. Reinstall pro-chat.".to_string()
+      }
+      SidecarError::Config { path, .. } => {
+        format!("Fix or delete {}, then retry.", path.display())
+      }
+      SidecarError::PortUnavailable(_) => {
+        "No local port could be reserved. Close other network-heavy apps and retry.".to_string()
+      }
+      SidecarError::Spawn(_) => "Check the logs for details, then retry.".to_string(),
+      SidecarError::HealthTimeout { .. } => {
+        "The API server started but never became ready. Check the logs, then retry.".to_string()
+      }
+      SidecarError::CrashLoop { .. } => {
+        "The API server exits as soon as it starts. Check the logs for why, then retry.".to_string()
+      }
+      SidecarError::Migration { .. } => {
+        "The database was left as it was before the failed step. Check the logs, then retry."
+          .to_string()
+      }
+      SidecarError::DatabaseCorrupt { .. } => {
+        "Restore the latest backup or snapshot, or salvage what can still be read into a new \
+         database. Either way the damaged file is kept."
+          .to_string()
+      }
+      SidecarError::RemoteUnreachable { .. } => {
+        "Check that the server is running and reachable from this computer, or start pro-chat \
+         with --local to use the built-in server."
+          .to_string()
+      }
+      SidecarError::UpgradeFailed {
+        from,
+        backup,
+        restored: true,
+        ..
+      } => format!(
+        "Your database was put back the way it was before the upgrade, so nothing was lost. \
+         Retry, or reinstall pro-chat {from} to keep using it. The copy taken before the \
+         upgrade is kept at {}.",
+        backup.display()
+      ),
+      SidecarError::UpgradeFailed {
+        database, backup, ..
+      } => format!(
+        "Putting the database back failed as well. Quit pro-chat and copy {} over {} before \
+         retrying.",
+        backup.display(),
+        database.display()
+      ),
+    }
+  }
+}
+
+impl fmt::Display for SidecarError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SidecarError::ResourceDirUnavailable(err) => {
+        write!(f, "Could not locate the app's resources: {err}")
+      }
+      SidecarError::DataDirUnavailable(err) => {
+        write!(f, "Could not locate the app data directory: {err}")
+      }
+      SidecarError::DataDirUnwritable { path, source } => {
+        write!(f, "Could not write to {}: {source}", path.display())
+      }
+      SidecarError::MissingEntry { path } => {
+        write!(f, "API entry not found at {}", path.display())
+      }
+      SidecarError::NodeNotFound { command } => {
+        write!(f, "Node.js runtime not found ({})", command.display())
+      }
+      SidecarError::NodeIncompatible {
+        command,
+        found,
+        expected,
+      } => write!(
+        f,
+        "{} is Node.js {found}, but pro-chat needs {expected}",
+        command.display()
+      ),
+      SidecarError::Integrity { path, reason } => {
+        write!(f, "Integrity check failed for {}: {reason}", path.display())
+      }
+      SidecarError::Config { path, message } => {
+        write!(f, "Invalid configuration in {}: {message}", path.display())
+      }
+      SidecarError::PortUnavailable(err) => write!(f, "Could not reserve a local port: {err}"),
+      SidecarError::Spawn(err) => write!(f, "Failed to launch the API server: {err}"),
+      SidecarError::HealthTimeout { seconds } => {
+        write!(f, "The API server did not respond within {seconds} seconds.")
+      }
+      SidecarError::CrashLoop {
+        exit_code: Some(code),
+      } => write!(f, "The API server kept exiting during startup (last exit code {code})."),
+      SidecarError::CrashLoop { exit_code: None } => {
+        write!(f, "The API server kept exiting during startup.")
+      }
+      SidecarError::Migration {
+        database,
+        name: Some(name),
+        reason,
+      } => write!(f, "Migration {name} failed on {}: {reason}", database.display()),
+      SidecarError::Migration {
+        database, reason, ..
+      } => write!(f, "Could not prepare {} for migrations: {reason}", database.display()),
+      SidecarError::DatabaseCorrupt { path, problems } => {
+        write!(f, "{} is damaged", path.display())?;
+        match problems.as_slice() {
+          [] => Ok(()),
+          [only] => write!(f, ": {only}"),
+          [first, rest @ ..] => write!(f, ": {first} (and {} more problems)", rest.len()),
+        }
+      }
+      SidecarError::RemoteUnreachable { url, reason } => {
+        write!(f, "Could not reach the API at {url}: {reason}")
+      }
+      SidecarError::UpgradeFailed {
+        from, to, reason, ..
+      } => write!(f, "pro-chat {to} could not upgrade your data from {from}: {reason}"),
+    }
+  }
+}
+
+impl std::error::Error for SidecarError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      SidecarError::ResourceDirUnavailable(err) | SidecarError::DataDirUnavailable(err) => {
+        Some(err)
+      }
+      SidecarError::DataDirUnwritable { source, .. } => Some(source),
+      SidecarError::PortUnavailable(err) | SidecarError::Spawn(err) => Some(err),
+      _ => None,
+    }
+  }
+}
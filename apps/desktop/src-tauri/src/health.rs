@@ -0,0 +1,22 @@
+use std::io::{Read, Write};
+use std::time::Duration;
+
+use crate::transport::{Connection, Endpoint};
+
+const HEALTH_REQUEST: &[u8] =
+  b"GET /api/health HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n";
+
+/// Sends `GET /api/health` to the sidecar and reports whether it answered 200.
+pub fn probe(endpoint: &Endpoint, timeout: Duration) -> bool {
+  let Ok(mut stream) = Connection::open(endpoint, timeout) else {
+    return false;
+  };
+  let _ = stream.set_read_timeout(Some(timeout));
+  if stream.write_all(HEALTH_REQUEST).is_err() {
+    return false;
+  }
+
+  // "HTTP/1.1 200" is all we need from the status line.
+  let mut status_line = [0u8; 12];
+  stream.read_exact(&mut status_line).is_ok() && &status_line[9..] == b"200"
+}
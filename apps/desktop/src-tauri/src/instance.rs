@@ -0,0 +1,159 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::paths;
+use crate::sidecar::generate_token;
+use crate::startup::{StartupState, MAIN_WINDOW, SPLASH_WINDOW};
+
+const LOCK_FILE: &str = "instance.lock";
+// Loopback port the primary instance listens on for forwarded launches, and
+// on the next line the nonce a forwarded launch has to open with. Any local
+// process can reach the port; only the user can read this file.
+const PORT_FILE: &str = "instance.port";
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(2);
+// Flags that choose the backend, which only happens when pro-chat starts.
+const LAUNCH_FLAGS: &[&str] = &["--local", "--remote", "--profile"];
+
+/// Exclusive lock on `instance.lock`, held for the lifetime of the primary
+/// instance so a second launch never touches `pro-chat.db` or the API port.
+pub struct InstanceLock {
+  _file: File,
+}
+
+/// Takes the single-instance lock, or returns `None` when another pro-chat
+/// already holds it.
+pub fn acquire(app: &AppHandle, data_dir: &Path) -> io::Result<Option<InstanceLock>> {
+  fs::create_dir_all(data_dir)?;
+  let file = OpenOptions::new()
+    .create(true)
+    .truncate(false)
+    .write(true)
+    .open(data_dir.join(LOCK_FILE))?;
+  match file.try_lock() {
+    Ok(()) => {
+      listen(app, data_dir)?;
+      Ok(Some(InstanceLock { _file: file }))
+    }
+    Err(TryLockError::WouldBlock) => Ok(None),
+    Err(TryLockError::Error(err)) => Err(err),
+  }
+}
+
+/// Hands this launch's arguments to the running instance, which focuses its
+/// window in response and says so if they asked for a different backend.
+pub fn forward_args(data_dir: &Path) -> io::Result<()> {
+  let contents = fs::read_to_string(data_dir.join(PORT_FILE))?;
+  let (port, nonce) = parse_port_file(&contents)
+    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed instance.port"))?;
+  let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+  let mut stream = TcpStream::connect_timeout(&addr, FORWARD_TIMEOUT)?;
+  writeln!(stream, "{nonce}")?;
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  serde_json::to_writer(stream, &args)?;
+  Ok(())
+}
+
+fn listen(app: &AppHandle, data_dir: &Path) -> io::Result<()> {
+  let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+  let nonce = generate_token();
+  let port = listener.local_addr()?.port();
+  paths::write_private(&data_dir.join(PORT_FILE), format!("{port}\n{nonce}\n").as_bytes())?;
+
+  let app = app.clone();
+  thread::spawn(move || {
+    for stream in listener.incoming().flatten() {
+      let _ = stream.set_read_timeout(Some(FORWARD_TIMEOUT));
+      let mut payload = Vec::new();
+      if stream.take(64 * 1024).read_to_end(&mut payload).is_err() {
+        continue;
+      }
+      let Some(args) = parse_forward(&payload, &nonce) else {
+        log::warn!("Ignoring a connection to the instance port without the right nonce");
+        continue;
+      };
+      log::info!("Another launch was redirected here with args {args:?}");
+      focus(&app);
+      if let Some(flag) = launch_flag(&args) {
+        let _ = app
+          .notification()
+          .builder()
+          .title("pro-chat is already running")
+          .body(format!(
+            "{flag} only applies when pro-chat starts. Quit pro-chat and launch it again to use it."
+          ))
+          .show();
+      }
+    }
+  });
+  Ok(())
+}
+
+/// The port and nonce `listen` wrote.
+fn parse_port_file(contents: &str) -> Option<(u16, &str)> {
+  let mut lines = contents.lines();
+  let port = lines.next()?.trim().parse().ok()?;
+  let nonce = lines.next()?.trim();
+  (!nonce.is_empty()).then_some((port, nonce))
+}
+
+/// The forwarded arguments, if the message opens with `nonce` on a line of
+/// its own.
+fn parse_forward(payload: &[u8], nonce: &str) -> Option<Vec<String>> {
+  let newline = payload.iter().position(|byte| *byte == b'\n')?;
+  let (first, rest) = payload.split_at(newline);
+  if first != nonce.as_bytes() {
+    return None;
+  }
+  Some(serde_json::from_slice(&rest[1..]).unwrap_or_default())
+}
+
+fn launch_flag(args: &[String]) -> Option<&'static str> {
+  args.iter().find_map(|arg| {
+    let name = arg.split('=').next()?;
+    LAUNCH_FLAGS.iter().copied().find(|flag| *flag == name)
+  })
+}
+
+fn focus(app: &AppHandle) {
+  let ready = app
+    .try_state::<StartupState>()
+    .is_some_and(|state| state.is_ready());
+  let label = if ready { MAIN_WINDOW } else { SPLASH_WINDOW };
+  if let Some(window) = app.get_webview_window(label) {
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn port_file_needs_a_nonce() {
+    assert_eq!(parse_port_file("4242\nabc123\n"), Some((4242, "abc123")));
+    assert_eq!(parse_port_file("4242\n"), None);
+    assert_eq!(parse_port_file("4242\n\n"), None);
+    assert_eq!(parse_port_file("port\nabc123\n"), None);
+  }
+
+  #[test]
+  fn forwarded_args_must_open_with_the_nonce() {
+    let args = parse_forward(b"abc123\n[\"--remote\",\"https://x\"]", "abc123");
+    assert_eq!(args, Some(vec!["--remote".to_string(), "https://x".to_string()]));
+
+    assert_eq!(parse_forward(b"[\"--remote\",\"https://x\"]", "abc123"), None);
+    assert_eq!(parse_forward(b"wrong\n[\"--remote\"]", "abc123"), None);
+    assert_eq!(parse_forward(b"abc12\n[\"--remote\"]", "abc123"), None);
+    assert_eq!(parse_forward(b"abc1234\n[\"--remote\"]", "abc123"), None);
+    assert_eq!(parse_forward(b"", "abc123"), None);
+  }
+}
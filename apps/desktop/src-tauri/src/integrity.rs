@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, Once};
+use std::time::SystemTime;
+
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::error::SidecarError;
+
+mod manifest {
+  // Generated by build.rs: NODE_SHA256, NODE_MAJOR and API_FILES.
+  include!(concat!(env!("OUT_DIR"), "/integrity.rs"));
+}
+
+// Oldest Node the API supports when the build recorded no bundled runtime.
+const MIN_NODE_MAJOR: u32 = 20;
+
+// Files already hashed this session, keyed by path, with the size and mtime
+// they had then. Hashing the runtime on every restart would add seconds.
+static VERIFIED: Mutex<BTreeMap<PathBuf, (u64, Option<SystemTime>)>> = Mutex::new(BTreeMap::new());
+static SYSTEM_NODE_WARNING: Once = Once::new();
+
+/// Checks `api_dir/dist` and, when given, the bundled Node runtime against the
+/// checksums recorded at build time. `node_modules` is not covered; it is too
+/// large to hash on every launch.
+pub fn verify_bundle(api_dir: &Path, bundled_node: Option<&Path>) -> Result<(), SidecarError> {
+  let dist = api_dir.join("dist");
+  if manifest::API_FILES.is_empty() {
+    if cfg!(debug_assertions) {
+      log::warn!("This build recorded no checksums; skipping the bundle integrity check");
+      return Ok(());
+    }
+    return Err(SidecarError::Integrity {
+      path: dist,
+      reason: "no checksums were recorded when the app was built".to_string(),
+    });
+  }
+  for (relative, expected) in manifest::API_FILES {
+    verify_file(&dist.join(relative), expected)?;
+  }
+  if let Some(node) = bundled_node {
+    match manifest::NODE_SHA256 {
+      Some(expected) => verify_file(node, expected)?,
+      None => {
+        return Err(SidecarError::Integrity {
+          path: node.to_path_buf(),
+          reason: "no checksum was recorded when the app was built".to_string(),
+        })
+      }
+    }
+  }
+  Ok(())
+}
+
+fn verify_file(path: &Path, expected: &str) -> Result<(), SidecarError> {
+  let fail = |reason: String| SidecarError::Integrity {
+    path: path.to_path_buf(),
+    reason,
+  };
+  let metadata = fs::metadata(path).map_err(|err| fail(err.to_string()))?;
+  let stamp = (metadata.len(), metadata.modified().ok());
+  let cached = VERIFIED
+    .lock()
+    .is_ok_and(|verified| verified.get(path) == Some(&stamp));
+  if cached {
+    return Ok(());
+  }
+  let actual = sha256_file(path).map_err(|err| fail(err.to_string()))?;
+  if actual != expected {
+    return Err(fail("its checksum does not match this build".to_string()));
+  }
+  if let Ok(mut verified) = VERIFIED.lock() {
+    verified.insert(path.to_path_buf(), stamp);
+  }
+  Ok(())
+}
+
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+  let mut hasher = Sha256::new();
+  io::copy(&mut File::open(path)?, &mut hasher)?;
+  Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Runs `<command> --version` and checks it is a Node the API can run on. With
+/// `match_bundled`, the major version must equal the runtime the app was built
+/// with; otherwise (or if none was recorded) it must be at least v20.
+pub fn check_node_version(command: &Path, match_bundled: bool) -> Result<String, SidecarError> {
+  let output = Command::new(command)
+    .arg("--version")
+    .output()
+    .map_err(|err| match err.kind() {
+      io::ErrorKind::NotFound => SidecarError::NodeNotFound {
+        command: command.to_path_buf(),
+      },
+      _ => SidecarError::Spawn(err),
+    })?;
+  let found = String::from_utf8_lossy(&output.stdout).trim().to_string();
+  let major = parse_major(&found);
+  let (compatible, expected) = match manifest::NODE_MAJOR.filter(|_| match_bundled) {
+    Some(expected) => (major == Some(expected), format!("v{expected}.x")),
+    None => (
+      major.is_some_and(|major| major >= MIN_NODE_MAJOR),
+      format!("v{MIN_NODE_MAJOR} or newer"),
+    ),
+  };
+  if !compatible {
+    return Err(SidecarError::NodeIncompatible {
+      command: command.to_path_buf(),
+      found: if found.is_empty() { "unknown".to_string() } else { found },
+      expected,
+    });
+  }
+  Ok(found)
+}
+
+/// Tells the user, once per session, that the API runs on a Node.js the app
+/// didn't ship with.
+pub fn warn_system_node(app: &AppHandle, version: &str) {
+  SYSTEM_NODE_WARNING.call_once(|| {
+    log::warn!("Bundled Node.js runtime is missing; falling back to node {version} from PATH");
+    let _ = app
+      .notification()
+      .builder()
+      .title("pro-chat is using your system Node.js")
+      .body(format!(
+        "The bundled runtime is missing, so the API runs on Node {version} from your PATH. \
+         Reinstall pro-chat to restore it."
+      ))
+      .show();
+  });
+}
+
+fn parse_major(version: &str) -> Option<u32> {
+  version.trim().trim_start_matches('v').split('.').next()?.parse().ok()
+}
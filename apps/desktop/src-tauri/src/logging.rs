@@ -0,0 +1,309 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use chrono::{SecondsFormat, Utc};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+pub const LOG_FILE: &str = "pro-chat.log";
+pub const ENTRY_EVENT: &str = "logs://entry";
+// Lines kept in memory for the log viewer; older ones are only on disk.
+const HISTORY_LIMIT: usize = 2000;
+// Each file is capped at MAX_FILE_BYTES; KEEP_FILES rotated copies are kept.
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+const KEEP_FILES: usize = 5;
+
+pub const HOST_SOURCE: &str = "host";
+pub const API_STDOUT: &str = "api:stdout";
+pub const API_STDERR: &str = "api:stderr";
+
+struct RotatingFile {
+  path: PathBuf,
+  file: File,
+  size: u64,
+}
+
+impl RotatingFile {
+  fn open(path: PathBuf) -> io::Result<Self> {
+    let file = OpenOptions::new().create(true).append(true).open(&path)?;
+    let size = file.metadata()?.len();
+    Ok(RotatingFile { path, file, size })
+  }
+
+  fn write_line(&mut self, line: &str) -> io::Result<()> {
+    if self.size + line.len() as u64 > MAX_FILE_BYTES {
+      self.rotate()?;
+    }
+    self.file.write_all(line.as_bytes())?;
+    self.size += line.len() as u64;
+    Ok(())
+  }
+
+  /// Shifts `pro-chat.log.N` to `.N+1`, dropping the oldest, and starts a
+  /// fresh `pro-chat.log`.
+  fn rotate(&mut self) -> io::Result<()> {
+    self.file.flush()?;
+    for index in (1..KEEP_FILES).rev() {
+      let from = rotated_path(&self.path, index);
+      if from.exists() {
+        fs::rename(&from, rotated_path(&self.path, index + 1))?;
+      }
+    }
+    fs::rename(&self.path, rotated_path(&self.path, 1))?;
+    *self = RotatingFile::open(self.path.clone())?;
+    Ok(())
+  }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+  let mut name = path.as_os_str().to_owned();
+  name.push(format!(".{index}"));
+  PathBuf::from(name)
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+  pub timestamp: String,
+  pub level: String,
+  pub source: String,
+  pub message: String,
+}
+
+impl LogEntry {
+  fn new(level: Level, source: &str, message: String) -> Self {
+    LogEntry {
+      timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+      level: level.as_str().to_lowercase(),
+      source: source.to_string(),
+      message,
+    }
+  }
+}
+
+/// Recent entries for the in-app log viewer, managed as Tauri state.
+#[derive(Clone, Default)]
+pub struct LogHistory(Arc<Mutex<VecDeque<LogEntry>>>);
+
+impl LogHistory {
+  /// The last `limit` entries, oldest first.
+  pub fn recent(&self, limit: usize) -> Vec<LogEntry> {
+    match self.0.lock() {
+      Ok(entries) => entries.iter().skip(entries.len().saturating_sub(limit)).cloned().collect(),
+      Err(_) => Vec::new(),
+    }
+  }
+
+  fn push(&self, entry: LogEntry) {
+    if let Ok(mut entries) = self.0.lock() {
+      entries.push_back(entry);
+      if entries.len() > HISTORY_LIMIT {
+        entries.pop_front();
+      }
+    }
+  }
+}
+
+thread_local! {
+  // Set while an entry is being emitted so anything logged by `emit` itself
+  // doesn't recurse back into the webview.
+  static EMITTING: Cell<bool> = const { Cell::new(false) };
+}
+
+struct FileLogger {
+  file: Mutex<RotatingFile>,
+  history: LogHistory,
+  app: AppHandle,
+}
+
+impl Log for FileLogger {
+  fn enabled(&self, metadata: &Metadata) -> bool {
+    metadata.level() <= Level::Info || metadata.target().starts_with("pro_chat")
+  }
+
+  fn log(&self, record: &Record) {
+    if !self.enabled(record.metadata()) {
+      return;
+    }
+    let source = if record.target().starts_with("api:") {
+      record.target()
+    } else {
+      HOST_SOURCE
+    };
+    let entry = LogEntry::new(record.level(), source, record.args().to_string());
+    let line = format!(
+      "{} {:<5} [{}] {}\n",
+      entry.timestamp,
+      record.level(),
+      entry.source,
+      entry.message
+    );
+    // Only debug builds have a terminal worth echoing to (`tauri dev`).
+    if cfg!(debug_assertions) {
+      eprint!("{line}");
+    }
+    if let Ok(mut file) = self.file.lock() {
+      let _ = file.write_line(&line);
+    }
+    self.history.push(entry.clone());
+
+    if !EMITTING.with(|flag| flag.replace(true)) {
+      let _ = self.app.emit(ENTRY_EVENT, entry);
+      EMITTING.with(|flag| flag.set(false));
+    }
+  }
+
+  fn flush(&self) {
+    if let Ok(mut file) = self.file.lock() {
+      let _ = file.file.flush();
+    }
+  }
+}
+
+/// Installs the global logger, writing to `<log_dir>/pro-chat.log` and
+/// streaming each entry to the webview as `logs://entry`.
+pub fn init(app: &AppHandle, log_dir: &Path) -> Result<LogHistory, Box<dyn std::error::Error>> {
+  fs::create_dir_all(log_dir)?;
+  let file = RotatingFile::open(log_dir.join(LOG_FILE))?;
+  let history = LogHistory::default();
+  log::set_boxed_logger(Box::new(FileLogger {
+    file: Mutex::new(file),
+    history: history.clone(),
+    app: app.clone(),
+  }))?;
+  log::set_max_level(LevelFilter::Debug);
+  Ok(history)
+}
+
+/// Returns up to `limit` of the most recent entries at `level` or more severe,
+/// optionally restricted to a source (`host`, `api`, `api:stdout`, ...).
+#[tauri::command]
+pub fn recent_logs(
+  history: State<'_, LogHistory>,
+  level: Option<String>,
+  source: Option<String>,
+  limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+  let entries = history.0.lock().map_err(|err| err.to_string())?;
+  select(
+    entries.iter(),
+    level.as_deref(),
+    source.as_deref(),
+    limit.unwrap_or(500).min(HISTORY_LIMIT),
+  )
+}
+
+/// The last `limit` of `entries` that pass the `recent_logs` filters, oldest
+/// first. Levels are matched case-insensitively.
+fn select<'a>(
+  entries: impl DoubleEndedIterator<Item = &'a LogEntry>,
+  level: Option<&str>,
+  source: Option<&str>,
+  limit: usize,
+) -> Result<Vec<LogEntry>, String> {
+  let max_level = match level {
+    Some(level) => {
+      Level::from_str(level).map_err(|_| format!("Unknown log level: {level}"))?
+    }
+    None => Level::Debug,
+  };
+  let mut matches: Vec<LogEntry> = entries
+    .rev()
+    .filter(|entry| Level::from_str(&entry.level).is_ok_and(|entry_level| entry_level <= max_level))
+    .filter(|entry| source.is_none_or(|source| matches_source(entry, source)))
+    .take(limit)
+    .cloned()
+    .collect();
+  matches.reverse();
+  Ok(matches)
+}
+
+/// `api` matches both `api:stdout` and `api:stderr`.
+fn matches_source(entry: &LogEntry, source: &str) -> bool {
+  entry.source == source
+    || entry
+      .source
+      .strip_prefix(source)
+      .is_some_and(|rest| rest.starts_with(':'))
+}
+
+/// Forwards each line the sidecar writes on `stream` to the log under `source`.
+pub fn capture<R: Read + Send + 'static>(stream: R, source: &'static str, level: Level) {
+  thread::spawn(move || {
+    let mut reader = BufReader::new(stream);
+    let mut buffer = Vec::new();
+    loop {
+      buffer.clear();
+      match reader.read_until(b'\n', &mut buffer) {
+        Ok(0) | Err(_) => return,
+        Ok(_) => {
+          let line = String::from_utf8_lossy(&buffer);
+          log::log!(target: source, level, "{}", line.trim_end());
+        }
+      }
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entry(source: &str) -> LogEntry {
+    LogEntry::new(Level::Info, source, String::new())
+  }
+
+  fn messages(entries: Vec<LogEntry>) -> Vec<String> {
+    entries.into_iter().map(|entry| entry.message).collect()
+  }
+
+  #[test]
+  fn source_matches_itself_and_its_streams() {
+    assert!(matches_source(&entry("host"), "host"));
+    assert!(matches_source(&entry(API_STDOUT), "api"));
+    assert!(matches_source(&entry(API_STDERR), "api"));
+    assert!(matches_source(&entry(API_STDERR), API_STDERR));
+  }
+
+  #[test]
+  fn source_does_not_match_a_prefix_or_sibling() {
+    assert!(!matches_source(&entry("apiary"), "api"));
+    assert!(!matches_source(&entry(API_STDOUT), API_STDERR));
+    assert!(!matches_source(&entry("api"), "api:stdout"));
+  }
+
+  #[test]
+  fn level_filter_matches_the_entries_as_written() {
+    let entries = [
+      LogEntry::new(Level::Debug, HOST_SOURCE, "debug".to_string()),
+      LogEntry::new(Level::Info, HOST_SOURCE, "info".to_string()),
+      LogEntry::new(Level::Warn, API_STDERR, "warn".to_string()),
+      LogEntry::new(Level::Error, HOST_SOURCE, "error".to_string()),
+    ];
+    assert_eq!(entries[2].level, "warn");
+    for level in ["warn", "WARN", "Warn"] {
+      let selected = select(entries.iter(), Some(level), None, 10).unwrap();
+      assert_eq!(messages(selected), ["warn", "error"], "{level}");
+    }
+    let selected = select(entries.iter(), None, None, 10).unwrap();
+    assert_eq!(selected.len(), 4);
+    assert!(select(entries.iter(), Some("loud"), None, 10).is_err());
+  }
+
+  #[test]
+  fn select_keeps_the_newest_matches_in_order() {
+    let entries: Vec<LogEntry> = (0..5)
+      .map(|n| LogEntry::new(Level::Info, API_STDOUT, n.to_string()))
+      .chain([LogEntry::new(Level::Info, HOST_SOURCE, "host".to_string())])
+      .collect();
+    let selected = select(entries.iter(), Some("info"), Some("api"), 2).unwrap();
+    assert_eq!(messages(selected), ["3", "4"]);
+  }
+}
@@ -1,91 +1,200 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::fs;
-use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
-use std::sync::{Arc, Mutex};
-use tauri::{Manager, WindowEvent};
+mod backup;
+mod clock;
+mod config;
+mod db;
+mod destination;
+mod devwatch;
+mod error;
+mod health;
+mod instance;
+mod integrity;
+mod logging;
+mod migrate;
+mod paths;
+mod pidfile;
+mod proxy;
+mod remote;
+mod repair;
+mod resources;
+mod sidecar;
+mod signals;
+mod snapshot;
+mod startup;
+#[cfg(test)]
+mod test_support;
+mod transport;
+mod upgrade;
+mod watchdog;
 
-struct ApiProcess(Arc<Mutex<Option<Child>>>);
+use std::collections::BTreeMap;
 
-fn spawn_api(app: &tauri::AppHandle) -> Result<Child, Box<dyn std::error::Error>> {
-  let resource_dir = app
-    .path()
-    .resource_dir()
-    .map_err(|err| -> Box<dyn std::error::Error> { Box::new(err) })?;
-  let app_data_dir = app
-    .path()
-    .app_data_dir()
-    .map_err(|err| -> Box<dyn std::error::Error> { Box::new(err) })?;
-  fs::create_dir_all(&app_data_dir)?;
+use remote::RemoteBackend;
+use serde::Serialize;
+use sidecar::{ApiProcess, SidecarMode};
+use startup::{StartupState, SPLASH_WINDOW};
+use tauri::{Manager, RunEvent, WindowEvent};
 
-  let api_dir = resource_dir.join("api");
-  let entry = api_dir.join("dist").join("index.js");
-  let node_modules = resource_dir.join("node_modules");
-  let storage_root = app_data_dir.join("storage");
-  let memory_root = app_data_dir.join("memory");
-  fs::create_dir_all(&storage_root)?;
-  fs::create_dir_all(&memory_root)?;
-
-  let db_path = app_data_dir.join("pro-chat.db");
-  let db_url = format!(
-    "file://{}",
-    db_path.to_string_lossy().replace(' ', "%20")
-  );
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiConnection {
+  /// `None` when no sidecar is running (for example with
+  /// `PRO_CHAT_SIDECAR=external`, where the Vite proxy handles `/api`).
+  base_url: Option<String>,
+  /// Headers the webview adds to every API request.
+  headers: BTreeMap<String, String>,
+  /// Whether chat streams must go through `api_stream`, because `base_url`
+  /// is the `prochat-api://` protocol and its responses arrive in one piece.
+  stream_via_host: bool,
+}
 
-  if !entry.exists() {
-    return Err(format!("API entry not found at {}", entry.display()).into());
+/// Where the webview should send API requests and the headers that
+/// authenticate them. A remote backend is always reached through the
+/// `prochat-api://` proxy, which adds the profile's token, so the webview never
+/// sees it. The sidecar's token is only handed out when it listens on a port;
+/// behind the socket proxy the host adds that one too.
+#[tauri::command]
+fn api_connection(app: tauri::AppHandle) -> ApiConnection {
+  let mut headers = BTreeMap::new();
+  let remote = app.try_state::<RemoteBackend>();
+  if remote.is_some_and(|remote| remote.target().is_some()) {
+    return ApiConnection {
+      base_url: Some(proxy::BASE_URL.to_string()),
+      headers,
+      stream_via_host: true,
+    };
   }
-
-  let bundled_node = resource_dir.join("bin").join("node");
-  let node_command: PathBuf = if bundled_node.exists() {
-    bundled_node
-  } else {
-    PathBuf::from("node")
+  let Some(process) = app.try_state::<ApiProcess>() else {
+    return ApiConnection {
+      base_url: None,
+      headers,
+      stream_via_host: false,
+    };
   };
-
-  let child = Command::new(node_command)
-    .arg(entry)
-    .current_dir(&api_dir)
-    .env("NODE_PATH", &node_modules)
-    .env("DATABASE_URL", db_url)
-    .env("STORAGE_PATH", storage_root)
-    .env("MEMORY_PATH", memory_root)
-    .stdin(Stdio::null())
-    .stdout(Stdio::inherit())
-    .stderr(Stdio::inherit())
-    .spawn()?;
-
-  Ok(child)
+  let base_url = process.base_url();
+  let via_proxy = process
+    .endpoint()
+    .is_some_and(|endpoint| endpoint.socket().is_some());
+  if base_url.is_some() && !via_proxy {
+    headers.insert("Authorization".to_string(), format!("Bearer {}", process.token()));
+  }
+  ApiConnection {
+    base_url,
+    headers,
+    stream_via_host: via_proxy,
+  }
 }
 
 fn main() {
   tauri::Builder::default()
     .plugin(tauri_plugin_notification::init())
+    .manage(proxy::ProxyStreams::default())
+    .register_asynchronous_uri_scheme_protocol(proxy::SCHEME, |ctx, request, responder| {
+      let app = ctx.app_handle().clone();
+      std::thread::spawn(move || responder.respond(proxy::handle(&app, request)));
+    })
     .setup(|app| {
-      if !cfg!(debug_assertions) {
-        match spawn_api(&app.handle()) {
-          Ok(child) => {
-            app.manage(ApiProcess(Arc::new(Mutex::new(Some(child)))));
-          }
-          Err(err) => {
-            eprintln!("Failed to start API server: {err}");
+      let data_dir = paths::app_data_dir(app.handle())?;
+      let handle = app.handle().clone();
+      // Taken before logging starts, so a second launch never opens (and
+      // perhaps rotates) the log file the running instance writes to.
+      let lock = match instance::acquire(&handle, &data_dir) {
+        Ok(Some(lock)) => Ok(lock),
+        Ok(None) => {
+          eprintln!("pro-chat is already running; forwarding to the existing window");
+          if let Err(err) = instance::forward_args(&data_dir) {
+            eprintln!("Failed to reach the running instance: {err}");
           }
+          std::process::exit(0);
+        }
+        Err(err) => Err(err),
+      };
+      let log_dir = data_dir.join("logs");
+      match logging::init(app.handle(), &log_dir) {
+        Ok(history) => {
+          app.manage(history);
         }
+        Err(err) => {
+          eprintln!("Failed to initialize logging in {}: {err}", log_dir.display());
+          app.manage(logging::LogHistory::default());
+        }
+      }
+      app.manage(StartupState::default());
+      match lock {
+        Ok(lock) => {
+          app.manage(lock);
+        }
+        Err(err) => {
+          log::warn!("Failed to take the single-instance lock: {err}");
+        }
+      }
+      signals::exit_on_termination(handle.clone());
+      let remote = RemoteBackend::resolve(&data_dir, std::env::args().skip(1));
+      if let Some(target) = remote.target() {
+        log::info!("Using the remote API at {}", target.url);
       }
+      let process = ApiProcess::new(&handle, SidecarMode::detect());
+      if process.mode() == SidecarMode::Source && remote.target().is_none() {
+        devwatch::watch_api_source(process.clone(), SidecarMode::source_dir().join("src"));
+      }
+      app.manage(process);
+      app.manage(remote);
+      config::watch(handle.clone());
+      resources::monitor(handle.clone());
+      snapshot::schedule(handle.clone());
+      startup::begin(&handle);
+      watchdog::spawn(handle);
       Ok(())
     })
+    .invoke_handler(tauri::generate_handler![
+      api_connection,
+      startup::startup_status,
+      startup::retry_startup,
+      logging::recent_logs,
+      sidecar::api_status,
+      sidecar::restart_api,
+      sidecar::stop_api,
+      remote::remote_settings,
+      remote::save_remote_profile,
+      remote::delete_remote_profile,
+      remote::set_active_remote_profile,
+      remote::check_remote,
+      config::host_config,
+      config::update_host_config,
+      proxy::api_stream,
+      proxy::api_stream_cancel,
+      backup::list_backups,
+      backup::create_backup,
+      backup::restore_backup,
+      snapshot::snapshot_settings,
+      snapshot::save_snapshot_settings,
+      snapshot::run_snapshot,
+      snapshot::test_snapshot_destination,
+      repair::restore_latest_backup,
+      repair::salvage_database
+    ])
     .on_window_event(|window, event| {
       if let WindowEvent::CloseRequested { .. } = event {
-        if let Some(state) = window.app_handle().try_state::<ApiProcess>() {
-          if let Ok(mut guard) = state.0.lock() {
-            if let Some(mut child) = guard.take() {
-              let _ = child.kill();
-            }
-          }
+        let app = window.app_handle();
+        let ready = app
+          .try_state::<StartupState>()
+          .is_some_and(|state| state.is_ready());
+        // Closing the splash before startup finishes means the user gave up
+        // waiting; the main window is still hidden, so quit.
+        if window.label() == SPLASH_WINDOW && !ready {
+          app.exit(0);
         }
       }
     })
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    .build(tauri::generate_context!())
+    .expect("error while building tauri application")
+    .run(|app, event| {
+      // Every way out (last window closed, Cmd+Q, app.exit, signals) ends here.
+      if let RunEvent::Exit = event {
+        if let Some(state) = app.try_state::<ApiProcess>() {
+          state.stop();
+        }
+      }
+    });
 }
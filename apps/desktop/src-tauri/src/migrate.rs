@@ -0,0 +1,318 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rusqlite::{params, Connection};
+use sha2::{Digest, Sha256};
+
+use crate::clock;
+use crate::error::SidecarError;
+
+const MIGRATION_FILE: &str = "migration.sql";
+
+// The table Prisma Migrate keeps, with the same columns, so `prisma migrate
+// status` and the host agree about what a database has.
+const CREATE_TABLE: &str = r#"CREATE TABLE IF NOT EXISTS "_prisma_migrations" (
+  "id" TEXT PRIMARY KEY NOT NULL,
+  "checksum" TEXT NOT NULL,
+  "finished_at" DATETIME,
+  "migration_name" TEXT NOT NULL,
+  "logs" TEXT,
+  "rolled_back_at" DATETIME,
+  "started_at" DATETIME NOT NULL DEFAULT current_timestamp,
+  "applied_steps_count" INTEGER UNSIGNED NOT NULL DEFAULT 0
+)"#;
+
+/// One `prisma/migrations/<name>/migration.sql`.
+struct Migration {
+  name: String,
+  sql: String,
+  /// Hex SHA-256 of the file, as Prisma records it.
+  checksum: String,
+}
+
+/// Migrations under `dir`, oldest first. Prisma prefixes their directory
+/// names with a timestamp, so name order is apply order.
+fn load(dir: &Path) -> Result<Vec<Migration>, SidecarError> {
+  let entries = fs::read_dir(dir).map_err(|_| SidecarError::MissingEntry {
+    path: dir.to_path_buf(),
+  })?;
+  let mut paths: Vec<(String, PathBuf)> = entries
+    .flatten()
+    .map(|entry| (entry.file_name(), entry.path().join(MIGRATION_FILE)))
+    .filter(|(_, path)| path.is_file())
+    .filter_map(|(name, path)| Some((name.into_string().ok()?, path)))
+    .collect();
+  paths.sort();
+  paths
+    .into_iter()
+    .map(|(name, path)| {
+      let sql = fs::read_to_string(&path).map_err(|_| SidecarError::MissingEntry {
+        path: path.clone(),
+      })?;
+      let checksum = format!("{:x}", Sha256::digest(sql.as_bytes()));
+      Ok(Migration {
+        name,
+        sql,
+        checksum,
+      })
+    })
+    .collect()
+}
+
+/// Brings the database at `db_path` (created if missing) up to date with the
+/// migrations in `dir` and returns the names it applied. Each migration runs
+/// in its own transaction together with its bookkeeping row, so a failure
+/// leaves the database as the previous migration left it. Because of that,
+/// `PRAGMA foreign_keys` statements in a migration have no effect.
+///
+/// A database created by `prisma db push` has tables but no bookkeeping; the
+/// first migration, the baseline, is recorded as applied without running it,
+/// as `prisma migrate resolve --applied` would.
+pub fn run(db_path: &Path, dir: &Path) -> Result<Vec<String>, SidecarError> {
+  let migrations = load(dir)?;
+  let setup_failed = |err: rusqlite::Error| SidecarError::Migration {
+    database: db_path.to_path_buf(),
+    name: None,
+    reason: err.to_string(),
+  };
+  let mut conn = Connection::open(db_path).map_err(setup_failed)?;
+  conn.busy_timeout(Duration::from_secs(5)).map_err(setup_failed)?;
+
+  let baseline = needs_baseline(&conn).map_err(setup_failed)?;
+  conn.execute_batch(CREATE_TABLE).map_err(setup_failed)?;
+  if let Some(first) = migrations.first().filter(|_| baseline) {
+    log::info!("Database has no migration history; marking {} as applied", first.name);
+    record(&conn, first, 0).map_err(setup_failed)?;
+  }
+
+  let applied = applied(&conn).map_err(setup_failed)?;
+  for (name, checksum) in &applied {
+    match migrations.iter().find(|migration| migration.name == *name) {
+      Some(migration) if checksum.is_none() => {
+        return Err(SidecarError::Migration {
+          database: db_path.to_path_buf(),
+          name: Some(migration.name.clone()),
+          reason: "it was started by another tool and never finished".to_string(),
+        });
+      }
+      Some(migration) if checksum.as_deref() != Some(migration.checksum.as_str()) => {
+        log::warn!("Migration {name} was edited after it was applied");
+      }
+      Some(_) => {}
+      None => log::warn!("Database has migration {name}, which this version doesn't ship"),
+    }
+  }
+
+  let mut newly_applied = Vec::new();
+  for migration in &migrations {
+    if applied.iter().any(|(name, _)| *name == migration.name) {
+      continue;
+    }
+    log::info!("Applying migration {}", migration.name);
+    apply(&mut conn, migration).map_err(|err| SidecarError::Migration {
+      database: db_path.to_path_buf(),
+      name: Some(migration.name.clone()),
+      reason: err.to_string(),
+    })?;
+    newly_applied.push(migration.name.clone());
+  }
+  Ok(newly_applied)
+}
+
+fn needs_baseline(conn: &Connection) -> rusqlite::Result<bool> {
+  let table_exists = |condition: &str| {
+    let sql = format!(
+      "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND {condition})"
+    );
+    conn.query_row(&sql, [], |row| row.get::<_, bool>(0))
+  };
+  Ok(!table_exists("name = '_prisma_migrations'")? && table_exists("name NOT LIKE 'sqlite_%'")?)
+}
+
+/// Names of the migrations the database has, with their checksums; `None`
+/// stands for one that was started but never finished.
+fn applied(conn: &Connection) -> rusqlite::Result<Vec<(String, Option<String>)>> {
+  let mut stmt = conn.prepare(
+    "SELECT migration_name, checksum, finished_at IS NOT NULL FROM _prisma_migrations \
+     WHERE rolled_back_at IS NULL ORDER BY started_at",
+  )?;
+  let rows = stmt.query_map([], |row| {
+    let finished: bool = row.get(2)?;
+    Ok((row.get(0)?, finished.then(|| row.get(1)).transpose()?))
+  })?;
+  rows.collect()
+}
+
+fn apply(conn: &mut Connection, migration: &Migration) -> rusqlite::Result<()> {
+  let tx = conn.transaction()?;
+  tx.execute_batch(&migration.sql)?;
+  record(&tx, migration, 1)?;
+  tx.commit()
+}
+
+fn record(conn: &Connection, migration: &Migration, steps: u32) -> rusqlite::Result<()> {
+  let now = clock::unix_ms() as i64;
+  conn.execute(
+    "INSERT INTO _prisma_migrations \
+     (id, checksum, finished_at, migration_name, started_at, applied_steps_count) \
+     VALUES (?1, ?2, ?3, ?4, ?3, ?5)",
+    params![migration_id(), migration.checksum, now, migration.name, steps],
+  )?;
+  Ok(())
+}
+
+/// A random (version 4) UUID, which is what Prisma uses for the row id.
+fn migration_id() -> String {
+  let mut bytes = [0u8; 16];
+  getrandom::fill(&mut bytes).expect("the OS random number generator is unavailable");
+  bytes[6] = (bytes[6] & 0x0f) | 0x40;
+  bytes[8] = (bytes[8] & 0x3f) | 0x80;
+  let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+  format!(
+    "{}-{}-{}-{}-{}",
+    &hex[..8],
+    &hex[8..12],
+    &hex[12..16],
+    &hex[16..20],
+    &hex[20..]
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::TempDir;
+
+  const NOTE: &str = "CREATE TABLE \"Note\" (\"id\" TEXT NOT NULL PRIMARY KEY);\n";
+  const TAG: &str = "CREATE TABLE \"Tag\" (\"id\" TEXT NOT NULL PRIMARY KEY);\n";
+
+  /// Lays out `migrations` the way Prisma does, under `<tmp>/migrations`.
+  fn migrations(tmp: &TempDir, migrations: &[(&str, &str)]) -> PathBuf {
+    let dir = tmp.path().join("migrations");
+    for (name, sql) in migrations {
+      fs::create_dir_all(dir.join(name)).unwrap();
+      fs::write(dir.join(name).join(MIGRATION_FILE), sql).unwrap();
+    }
+    fs::write(dir.join("migration_lock.toml"), "provider = \"sqlite\"\n").unwrap();
+    dir
+  }
+
+  /// `(migration_name, checksum, finished, applied_steps_count)` per row.
+  fn history(db: &Path) -> Vec<(String, String, bool, u32)> {
+    let conn = Connection::open(db).unwrap();
+    let mut stmt = conn
+      .prepare(
+        "SELECT migration_name, checksum, finished_at IS NOT NULL, applied_steps_count \
+         FROM _prisma_migrations ORDER BY migration_name",
+      )
+      .unwrap();
+    let rows = stmt
+      .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+      .unwrap();
+    rows.collect::<rusqlite::Result<_>>().unwrap()
+  }
+
+  fn has_table(db: &Path, table: &str) -> bool {
+    Connection::open(db)
+      .unwrap()
+      .query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        [table],
+        |row| row.get(0),
+      )
+      .unwrap()
+  }
+
+  #[test]
+  fn fresh_database_gets_every_migration() {
+    let tmp = TempDir::new();
+    let dir = migrations(&tmp, &[("20260102000000_tag", TAG), ("20260101000000_note", NOTE)]);
+    let db = tmp.path().join("fresh.db");
+
+    assert_eq!(
+      run(&db, &dir).unwrap(),
+      ["20260101000000_note", "20260102000000_tag"]
+    );
+    assert!(has_table(&db, "Note") && has_table(&db, "Tag"));
+    let rows = history(&db);
+    assert_eq!(rows.len(), 2);
+    assert!(rows.iter().all(|(_, _, finished, steps)| *finished && *steps == 1));
+
+    assert!(run(&db, &dir).unwrap().is_empty());
+    assert_eq!(history(&db).len(), 2);
+  }
+
+  #[test]
+  fn db_push_database_only_records_the_baseline() {
+    let tmp = TempDir::new();
+    let dir = migrations(&tmp, &[("20260101000000_note", NOTE), ("20260102000000_tag", TAG)]);
+    let db = tmp.path().join("pushed.db");
+    Connection::open(&db).unwrap().execute_batch(NOTE).unwrap();
+
+    assert_eq!(run(&db, &dir).unwrap(), ["20260102000000_tag"]);
+    let rows = history(&db);
+    assert_eq!(rows[0].0, "20260101000000_note");
+    assert_eq!(rows[0].3, 0, "the baseline is recorded without running it");
+    assert_eq!(rows[1].0, "20260102000000_tag");
+    assert_eq!(rows[1].3, 1);
+  }
+
+  #[test]
+  fn failed_migration_rolls_back_with_its_record() {
+    let tmp = TempDir::new();
+    let broken = "CREATE TABLE \"Tag\" (\"id\" TEXT);\nINSERT INTO \"Missing\" VALUES (1);\n";
+    let dir = migrations(&tmp, &[("20260101000000_note", NOTE), ("20260102000000_tag", broken)]);
+    let db = tmp.path().join("broken.db");
+
+    let err = run(&db, &dir).unwrap_err();
+    assert!(matches!(
+      err,
+      SidecarError::Migration { name: Some(ref name), .. } if name == "20260102000000_tag"
+    ));
+    assert!(has_table(&db, "Note"));
+    assert!(!has_table(&db, "Tag"));
+    let names: Vec<String> = history(&db).into_iter().map(|row| row.0).collect();
+    assert_eq!(names, ["20260101000000_note"]);
+  }
+
+  #[test]
+  fn unfinished_migration_is_an_error() {
+    let tmp = TempDir::new();
+    let dir = migrations(&tmp, &[("20260101000000_note", NOTE)]);
+    let db = tmp.path().join("unfinished.db");
+    run(&db, &dir).unwrap();
+    Connection::open(&db)
+      .unwrap()
+      .execute("UPDATE _prisma_migrations SET finished_at = NULL", [])
+      .unwrap();
+
+    let err = run(&db, &dir).unwrap_err();
+    assert!(matches!(
+      err,
+      SidecarError::Migration { name: Some(ref name), .. } if name == "20260101000000_note"
+    ));
+  }
+
+  #[test]
+  fn checksum_is_the_sha256_prisma_records() {
+    let tmp = TempDir::new();
+    let dir = migrations(&tmp, &[("20260101000000_note", NOTE)]);
+    let expected = "432b69cf49bc0f3117b577d215d1a3f2a8f9000e0f7a52c29a2c3a3c244d6853";
+    assert_eq!(load(&dir).unwrap()[0].checksum, expected);
+
+    let db = tmp.path().join("checksum.db");
+    run(&db, &dir).unwrap();
+    assert_eq!(history(&db)[0].1, expected);
+  }
+
+  #[test]
+  fn shipped_migrations_apply_to_a_fresh_database() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../api/prisma/migrations");
+    let tmp = TempDir::new();
+    let db = tmp.path().join("shipped.db");
+    let applied = run(&db, &dir).unwrap();
+    assert!(!applied.is_empty());
+    assert!(has_table(&db, "User") && has_table(&db, "ChatThread"));
+  }
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, Manager};
+
+/// Where the app keeps its database, attachments, logs and locks. Debug builds
+/// use a `dev` subdirectory so `tauri dev` never touches the installed app's data.
+pub fn app_data_dir(app: &AppHandle) -> tauri::Result<PathBuf> {
+  let dir = app.path().app_data_dir()?;
+  Ok(if cfg!(debug_assertions) {
+    dir.join("dev")
+  } else {
+    dir
+  })
+}
+
+/// Replaces `path` with `contents` through a temporary file that is synced and
+/// then renamed, so a crash never leaves it empty or half-written. On Unix the
+/// file is created readable by its owner only, since several of these hold
+/// tokens or credentials.
+pub fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+  let mut tmp = path.as_os_str().to_owned();
+  tmp.push(".tmp");
+  let tmp = PathBuf::from(tmp);
+  // A leftover from a crash could have looser permissions; start afresh.
+  match fs::remove_file(&tmp) {
+    Ok(()) => {}
+    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+    Err(err) => return Err(err),
+  }
+  let mut options = fs::OpenOptions::new();
+  options.write(true).create_new(true);
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::OpenOptionsExt;
+    options.mode(0o600);
+  }
+  let written = options.open(&tmp).and_then(|mut file| {
+    file.write_all(contents)?;
+    file.sync_all()
+  });
+  if let Err(err) = written {
+    let _ = fs::remove_file(&tmp);
+    return Err(err);
+  }
+  fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::TempDir;
+
+  #[test]
+  fn write_private_replaces_the_file() {
+    let tmp = TempDir::new();
+    let path = tmp.path().join("remote.json");
+    fs::write(&path, b"old").unwrap();
+    fs::write(tmp.path().join("remote.json.tmp"), b"leftover").unwrap();
+
+    write_private(&path, b"new").unwrap();
+
+    assert_eq!(fs::read(&path).unwrap(), b"new");
+    assert!(!tmp.path().join("remote.json.tmp").exists());
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      let mode = fs::metadata(&path).unwrap().permissions().mode();
+      assert_eq!(mode & 0o777, 0o600);
+    }
+  }
+}
@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock;
+use crate::transport::Endpoint;
+
+const PID_FILE: &str = "sidecar.json";
+
+/// What the host knows about the sidecar it last spawned, persisted so the
+/// next launch can clean up after a host that died without shutting it down.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SidecarRecord {
+  pid: u32,
+  #[serde(default)]
+  port: Option<u16>,
+  #[serde(default)]
+  socket: Option<PathBuf>,
+  entry: PathBuf,
+  host_pid: u32,
+  started_at_ms: u64,
+}
+
+pub fn write(data_dir: &Path, pid: u32, endpoint: &Endpoint, entry: &Path) {
+  let record = SidecarRecord {
+    pid,
+    port: endpoint.port(),
+    socket: endpoint.socket().map(Path::to_path_buf),
+    entry: entry.to_path_buf(),
+    host_pid: std::process::id(),
+    started_at_ms: clock::unix_ms(),
+  };
+  let result = serde_json::to_vec_pretty(&record)
+    .map_err(std::io::Error::from)
+    .and_then(|bytes| fs::write(data_dir.join(PID_FILE), bytes));
+  if let Err(err) = result {
+    log::warn!("Failed to record sidecar pid: {err}");
+  }
+}
+
+pub fn clear(data_dir: &Path) {
+  let _ = fs::remove_file(data_dir.join(PID_FILE));
+}
+
+/// Terminates a sidecar left behind by a previous host, but only after
+/// confirming the pid still belongs to our `api/dist/index.js`.
+pub fn reap_stale(data_dir: &Path) {
+  let path = data_dir.join(PID_FILE);
+  let Ok(raw) = fs::read(&path) else {
+    return;
+  };
+  let record: SidecarRecord = match serde_json::from_slice(&raw) {
+    Ok(record) => record,
+    Err(err) => {
+      log::warn!("Ignoring unreadable sidecar record: {err}");
+      clear(data_dir);
+      return;
+    }
+  };
+  if record.host_pid != std::process::id() && is_our_sidecar(&record) {
+    log::warn!(
+      "Found orphaned API server from a previous run (pid {}); terminating it",
+      record.pid
+    );
+    terminate(record.pid);
+  }
+  clear(data_dir);
+}
+
+#[cfg(unix)]
+fn is_our_sidecar(record: &SidecarRecord) -> bool {
+  // SAFETY: signal 0 only checks whether the pid exists and is ours to signal.
+  let alive = unsafe { libc::kill(record.pid as libc::pid_t, 0) == 0 };
+  if !alive {
+    return false;
+  }
+  let entry = record.entry.to_string_lossy();
+  command_line(record.pid).is_some_and(|command| command.contains(entry.as_ref()))
+}
+
+#[cfg(not(unix))]
+fn is_our_sidecar(_record: &SidecarRecord) -> bool {
+  false
+}
+
+#[cfg(target_os = "linux")]
+fn command_line(pid: u32) -> Option<String> {
+  let raw = fs::read(format!("/proc/{pid}/cmdline")).ok()?;
+  Some(String::from_utf8_lossy(&raw).replace('\0', " "))
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn command_line(pid: u32) -> Option<String> {
+  let output = std::process::Command::new("ps")
+    .args(["-p", &pid.to_string(), "-o", "command="])
+    .output()
+    .ok()?;
+  output
+    .status
+    .success()
+    .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The orphan led its own process group, so signal the group to take any
+/// Python tool runs down with it.
+#[cfg(unix)]
+fn terminate(pid: u32) {
+  use std::thread;
+  use std::time::{Duration, Instant};
+
+  use crate::sidecar::{signal_group, SHUTDOWN_GRACE};
+
+  let pgid = pid as libc::pid_t;
+  signal_group(pgid, libc::SIGTERM);
+  let deadline = Instant::now() + SHUTDOWN_GRACE;
+  // SAFETY: signal 0 only probes for existence.
+  while Instant::now() < deadline && unsafe { libc::kill(pgid, 0) == 0 } {
+    thread::sleep(Duration::from_millis(50));
+  }
+  signal_group(pgid, libc::SIGKILL);
+}
+
+#[cfg(not(unix))]
+fn terminate(_pid: u32) {}
@@ -0,0 +1,680 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::http::{
+  header, HeaderMap, HeaderName, HeaderValue, Method, Request, Response, StatusCode,
+};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Manager, State};
+
+use crate::remote::{RemoteBackend, RemoteProfile};
+use crate::sidecar::ApiProcess;
+use crate::transport::Connection;
+
+pub const SCHEME: &str = "prochat-api";
+/// What `api_connection` hands the webview when the sidecar listens on a
+/// socket or the backend is remote. Windows webviews reach custom protocols
+/// through an `http://<scheme>.localhost` origin instead.
+#[cfg(not(windows))]
+pub const BASE_URL: &str = "prochat-api://localhost";
+#[cfg(windows)]
+pub const BASE_URL: &str = "http://prochat-api.localhost";
+
+// The only requests either entry point passes on.
+const API_PREFIX: &str = "/api/";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// Applies to buffered requests only; a streamed reply can take minutes.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(120);
+// Request headers the proxy sets itself (framing, auth) or that would make the
+// API add CORS headers of its own.
+const SKIPPED_REQUEST_HEADERS: &[&str] = &[
+  "host",
+  "connection",
+  "keep-alive",
+  "content-length",
+  "transfer-encoding",
+  "authorization",
+  "origin",
+];
+const SKIPPED_RESPONSE_HEADERS: &[&str] = &[
+  "connection",
+  "keep-alive",
+  "content-length",
+  "transfer-encoding",
+  "access-control-allow-origin",
+];
+
+/// Serves `prochat-api://localhost/...` by replaying the request to the
+/// sidecar with the host's token, or to the remote backend with the profile's.
+/// Tauri hands the webview protocol responses in one piece, so this is for
+/// ordinary requests; SSE goes through `api_stream` instead.
+pub fn handle(app: &AppHandle, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+  if request.method() == Method::OPTIONS {
+    return preflight();
+  }
+  if let Err(message) = check_path(request.uri().path()) {
+    return error_response(StatusCode::NOT_FOUND, &message);
+  }
+  match forward(app, &request) {
+    Ok(response) => response,
+    Err(err) => {
+      log::warn!(
+        "{} {} via {SCHEME}:// failed: {err}",
+        request.method(),
+        request.uri().path()
+      );
+      error_response(StatusCode::BAD_GATEWAY, &err.to_string())
+    }
+  }
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Vec<u8>> {
+  let body = serde_json::json!({ "error": message }).to_string();
+  Response::builder()
+    .status(status)
+    .header(header::CONTENT_TYPE, "application/json")
+    .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+    .body(body.into_bytes())
+    .unwrap_or_default()
+}
+
+/// Only `/api/` routes are reachable through the host, and a path can't carry
+/// anything that would end the request line early.
+fn check_path(path: &str) -> Result<(), String> {
+  if !path.starts_with(API_PREFIX) {
+    return Err(format!("Not an API path: {path}"));
+  }
+  if path.bytes().any(|byte| byte.is_ascii_whitespace() || byte.is_ascii_control()) {
+    return Err("The path contains whitespace or control characters".to_string());
+  }
+  Ok(())
+}
+
+/// Parses what the webview sent to `api_stream`. `Method`, `HeaderName` and
+/// `HeaderValue` all reject CR and LF, so nothing can be smuggled into the
+/// request head past the token the host adds.
+fn parse_request(
+  method: &str,
+  path: &str,
+  headers: &BTreeMap<String, String>,
+) -> Result<(Method, HeaderMap), String> {
+  check_path(path)?;
+  let method =
+    Method::from_bytes(method.as_bytes()).map_err(|_| format!("Invalid method: {method:?}"))?;
+  let mut parsed = HeaderMap::new();
+  for (name, value) in headers {
+    let header_name =
+      HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("Invalid header: {name:?}"))?;
+    let header_value =
+      HeaderValue::from_str(value).map_err(|_| format!("Invalid value for header {name}"))?;
+    parsed.append(header_name, header_value);
+  }
+  Ok((method, parsed))
+}
+
+fn preflight() -> Response<Vec<u8>> {
+  Response::builder()
+    .status(StatusCode::NO_CONTENT)
+    .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+    .header(header::ACCESS_CONTROL_ALLOW_HEADERS, "Content-Type")
+    .header(
+      header::ACCESS_CONTROL_ALLOW_METHODS,
+      "GET, POST, PUT, PATCH, DELETE, OPTIONS",
+    )
+    .body(Vec::new())
+    .unwrap_or_default()
+}
+
+fn forward(app: &AppHandle, request: &Request<Vec<u8>>) -> io::Result<Response<Vec<u8>>> {
+  let path = request.uri().path_and_query().map_or("/", |path| path.as_str());
+  let (head, body) = match upstream(app)? {
+    Upstream::Local(mut connection, token) => {
+      connection.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+      write_request(
+        &mut connection,
+        request.method(),
+        path,
+        request.headers(),
+        request.body(),
+        &token,
+      )?;
+      let mut reader = BufReader::new(connection);
+      let head = read_head(&mut reader)?;
+      let mut body = Vec::new();
+      read_body(&mut reader, &head, |chunk| {
+        body.extend_from_slice(chunk);
+        Ok(())
+      })?;
+      (head, body)
+    }
+    Upstream::Remote(target) => {
+      let response = send_remote(
+        &target,
+        request.method(),
+        path,
+        request.headers(),
+        request.body().clone(),
+        Some(RESPONSE_TIMEOUT),
+      )?;
+      let head = remote_head(&response);
+      let body = response.bytes().map_err(io::Error::other)?.to_vec();
+      (head, body)
+    }
+  };
+  let mut builder = Response::builder().status(head.status);
+  for (name, value) in head.forwarded_headers() {
+    builder = builder.header(name, value);
+  }
+  builder
+    .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+    .body(body)
+    .map_err(io::Error::other)
+}
+
+/// Where a proxied request goes. Either way the token is added here, so the
+/// webview never holds it.
+enum Upstream {
+  /// The sidecar, with its per-launch token.
+  Local(Connection, String),
+  /// The remote backend chosen at launch.
+  Remote(RemoteProfile),
+}
+
+fn upstream(app: &AppHandle) -> io::Result<Upstream> {
+  let remote = app.try_state::<RemoteBackend>();
+  if let Some(target) = remote.as_ref().and_then(|remote| remote.target()) {
+    return Ok(Upstream::Remote(target.clone()));
+  }
+  let not_running = || io::Error::new(io::ErrorKind::NotConnected, "the API server is not running");
+  let process = app.try_state::<ApiProcess>().ok_or_else(not_running)?;
+  let endpoint = process.endpoint().ok_or_else(not_running)?;
+  let connection = Connection::open(&endpoint, CONNECT_TIMEOUT)?;
+  Ok(Upstream::Local(connection, process.token().to_string()))
+}
+
+/// Sends a request to the remote backend with the profile's token. `timeout`
+/// covers the whole exchange, so streams pass `None`.
+fn send_remote(
+  target: &RemoteProfile,
+  method: &Method,
+  path: &str,
+  headers: &HeaderMap,
+  body: Vec<u8>,
+  timeout: Option<Duration>,
+) -> io::Result<reqwest::blocking::Response> {
+  let client = reqwest::blocking::Client::builder()
+    .connect_timeout(CONNECT_TIMEOUT)
+    .timeout(timeout)
+    .build()
+    .map_err(io::Error::other)?;
+  let headers: HeaderMap = headers
+    .iter()
+    .filter(|(name, _)| !SKIPPED_REQUEST_HEADERS.contains(&name.as_str()))
+    .map(|(name, value)| (name.clone(), value.clone()))
+    .collect();
+  let mut request = client
+    .request(method.clone(), format!("{}{path}", target.url))
+    .headers(headers)
+    .body(body);
+  if let Some(token) = &target.token {
+    request = request.bearer_auth(token);
+  }
+  request.send().map_err(io::Error::other)
+}
+
+fn remote_head(response: &reqwest::blocking::Response) -> Head {
+  Head {
+    status: response.status().as_u16(),
+    headers: response
+      .headers()
+      .iter()
+      .filter_map(|(name, value)| {
+        Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+      })
+      .collect(),
+  }
+}
+
+/// Writes an HTTP/1.1 request. `path` must have been through `check_path`.
+fn write_request(
+  connection: &mut impl Write,
+  method: &Method,
+  path: &str,
+  headers: &HeaderMap,
+  body: &[u8],
+  token: &str,
+) -> io::Result<()> {
+  let mut head = format!(
+    "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+     Authorization: Bearer {token}\r\nContent-Length: {}\r\n",
+    body.len()
+  )
+  .into_bytes();
+  for (name, value) in headers {
+    if SKIPPED_REQUEST_HEADERS.contains(&name.as_str()) {
+      continue;
+    }
+    head.extend_from_slice(name.as_str().as_bytes());
+    head.extend_from_slice(b": ");
+    head.extend_from_slice(value.as_bytes());
+    head.extend_from_slice(b"\r\n");
+  }
+  head.extend_from_slice(b"\r\n");
+  connection.write_all(&head)?;
+  connection.write_all(body)?;
+  connection.flush()
+}
+
+struct Head {
+  status: u16,
+  headers: Vec<(String, String)>,
+}
+
+impl Head {
+  fn header(&self, name: &str) -> Option<&str> {
+    self
+      .headers
+      .iter()
+      .find(|(header, _)| header.eq_ignore_ascii_case(name))
+      .map(|(_, value)| value.as_str())
+  }
+
+  fn forwarded_headers(&self) -> impl Iterator<Item = &(String, String)> {
+    self.headers.iter().filter(|(name, _)| {
+      !SKIPPED_RESPONSE_HEADERS
+        .iter()
+        .any(|skipped| name.eq_ignore_ascii_case(skipped))
+    })
+  }
+}
+
+fn malformed(what: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, format!("malformed {what} from the API server"))
+}
+
+fn read_head(reader: &mut impl BufRead) -> io::Result<Head> {
+  let mut line = String::new();
+  reader.read_line(&mut line)?;
+  let status = line
+    .split_whitespace()
+    .nth(1)
+    .and_then(|code| code.parse().ok())
+    .ok_or_else(|| malformed("status line"))?;
+  let mut headers = Vec::new();
+  loop {
+    line.clear();
+    if reader.read_line(&mut line)? == 0 {
+      return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    let line = line.trim_end();
+    if line.is_empty() {
+      break;
+    }
+    if let Some((name, value)) = line.split_once(':') {
+      headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+  }
+  Ok(Head { status, headers })
+}
+
+/// Feeds the response body to `on_chunk` as it arrives, undoing chunked
+/// transfer encoding. Express sends each SSE event as its own chunk.
+fn read_body(
+  reader: &mut impl BufRead,
+  head: &Head,
+  mut on_chunk: impl FnMut(&[u8]) -> io::Result<()>,
+) -> io::Result<()> {
+  let chunked = head
+    .header("transfer-encoding")
+    .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"));
+  if chunked {
+    let mut line = String::new();
+    loop {
+      line.clear();
+      if reader.read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+      }
+      let size = line.trim().split(';').next().unwrap_or_default();
+      let size = usize::from_str_radix(size, 16).map_err(|_| malformed("chunk size"))?;
+      if size == 0 {
+        return Ok(());
+      }
+      let mut chunk = vec![0; size];
+      reader.read_exact(&mut chunk)?;
+      on_chunk(&chunk)?;
+      let mut crlf = [0u8; 2];
+      reader.read_exact(&mut crlf)?;
+    }
+  }
+
+  let length = head
+    .header("content-length")
+    .and_then(|length| length.parse::<u64>().ok());
+  copy_body(reader, length, on_chunk)
+}
+
+/// Feeds `reader` to `on_chunk` as it arrives, up to `remaining` bytes when
+/// the length is known and to the end of the stream otherwise.
+fn copy_body(
+  reader: &mut impl Read,
+  mut remaining: Option<u64>,
+  mut on_chunk: impl FnMut(&[u8]) -> io::Result<()>,
+) -> io::Result<()> {
+  let mut buffer = [0u8; 16 * 1024];
+  loop {
+    let wanted = remaining.map_or(buffer.len(), |left| left.min(buffer.len() as u64) as usize);
+    if wanted == 0 {
+      return Ok(());
+    }
+    let read = reader.read(&mut buffer[..wanted])?;
+    if read == 0 {
+      return match remaining {
+        Some(left) if left > 0 => Err(io::ErrorKind::UnexpectedEof.into()),
+        _ => Ok(()),
+      };
+    }
+    on_chunk(&buffer[..read])?;
+    if let Some(left) = remaining.as_mut() {
+      *left -= read as u64;
+    }
+  }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum StreamMessage {
+  Head {
+    status: u16,
+    headers: Vec<(String, String)>,
+  },
+  Chunk {
+    data: String,
+  },
+  End,
+  Error {
+    message: String,
+  },
+}
+
+/// Streams in flight, so the webview can cancel one. Closing the socket is
+/// what tells the API the client went away.
+#[derive(Default)]
+pub struct ProxyStreams {
+  next_id: AtomicU64,
+  open: Mutex<BTreeMap<u64, OpenStream>>,
+}
+
+enum OpenStream {
+  /// A clone of the sidecar connection; shutting it down ends the relay.
+  Local(Connection),
+  /// reqwest has no way to close a connection from another thread, so a
+  /// remote stream is dropped at the next chunk after this is set.
+  Remote(Arc<AtomicBool>),
+}
+
+/// An `api_stream` request waiting for its relay thread.
+enum PendingStream {
+  Local(Connection),
+  Remote(RemoteProfile, Arc<AtomicBool>),
+}
+
+/// Sends a request to the sidecar and relays the response to `channel` as it
+/// arrives, for the SSE routes a `prochat-api://` response can't carry.
+/// Returns an id for `api_stream_cancel`.
+#[tauri::command]
+pub async fn api_stream(
+  app: AppHandle,
+  streams: State<'_, ProxyStreams>,
+  method: String,
+  path: String,
+  headers: BTreeMap<String, String>,
+  body: Option<String>,
+  channel: Channel<StreamMessage>,
+) -> Result<u64, String> {
+  let (method, headers) = parse_request(&method, &path, &headers)?;
+  let body = body.unwrap_or_default().into_bytes();
+  // Connecting can take up to the connect timeout; keep it off the runtime.
+  let opening = {
+    let app = app.clone();
+    let request = (method.clone(), path.clone(), headers.clone(), body.clone());
+    tauri::async_runtime::spawn_blocking(move || {
+      let (method, path, headers, body) = request;
+      open_stream(&app, &method, &path, &headers, &body)
+    })
+  };
+  let (pending, handle) = opening.await.map_err(|err| err.to_string())??;
+
+  let id = streams.next_id.fetch_add(1, Ordering::Relaxed);
+  if let Ok(mut open) = streams.open.lock() {
+    open.insert(id, handle);
+  }
+  thread::spawn(move || {
+    let result = match pending {
+      PendingStream::Local(connection) => relay_local(connection, &channel),
+      PendingStream::Remote(target, cancelled) => {
+        send_remote(&target, &method, &path, &headers, body, None)
+          .and_then(|response| relay_remote(response, &cancelled, &channel))
+      }
+    };
+    if let Err(err) = result {
+      let _ = channel.send(StreamMessage::Error {
+        message: err.to_string(),
+      });
+    }
+    if let Some(streams) = app.try_state::<ProxyStreams>() {
+      if let Ok(mut open) = streams.open.lock() {
+        open.remove(&id);
+      }
+    }
+  });
+  Ok(id)
+}
+
+/// Connects to the upstream server. A local request is written right away; a
+/// remote one is sent from the relay thread, since waiting for a remote server
+/// to answer would hold up the caller.
+fn open_stream(
+  app: &AppHandle,
+  method: &Method,
+  path: &str,
+  headers: &HeaderMap,
+  body: &[u8],
+) -> Result<(PendingStream, OpenStream), String> {
+  match upstream(app).map_err(|err| err.to_string())? {
+    Upstream::Local(mut connection, token) => {
+      write_request(&mut connection, method, path, headers, body, &token)
+        .map_err(|err| err.to_string())?;
+      let handle = connection.try_clone().map_err(|err| err.to_string())?;
+      Ok((PendingStream::Local(connection), OpenStream::Local(handle)))
+    }
+    Upstream::Remote(target) => {
+      let cancelled = Arc::new(AtomicBool::new(false));
+      Ok((
+        PendingStream::Remote(target, cancelled.clone()),
+        OpenStream::Remote(cancelled),
+      ))
+    }
+  }
+}
+
+#[tauri::command]
+pub fn api_stream_cancel(streams: State<'_, ProxyStreams>, id: u64) {
+  let stream = streams.open.lock().ok().and_then(|mut open| open.remove(&id));
+  match stream {
+    Some(OpenStream::Local(connection)) => connection.shutdown(),
+    Some(OpenStream::Remote(cancelled)) => cancelled.store(true, Ordering::Relaxed),
+    None => {}
+  }
+}
+
+fn relay_local(connection: Connection, channel: &Channel<StreamMessage>) -> io::Result<()> {
+  let mut reader = BufReader::new(connection);
+  let head = read_head(&mut reader)?;
+  relay(&head, channel, |on_chunk| read_body(&mut reader, &head, on_chunk))
+}
+
+fn relay_remote(
+  mut response: reqwest::blocking::Response,
+  cancelled: &AtomicBool,
+  channel: &Channel<StreamMessage>,
+) -> io::Result<()> {
+  let head = remote_head(&response);
+  relay(&head, channel, |on_chunk| {
+    // reqwest has already undone the transfer encoding.
+    copy_body(&mut response, None, |chunk| {
+      if cancelled.load(Ordering::Relaxed) {
+        return Err(io::Error::new(io::ErrorKind::Interrupted, "the stream was cancelled"));
+      }
+      on_chunk(chunk)
+    })
+  })
+}
+
+/// Sends `head`, then the body `read_body` produces as text chunks, then the
+/// end marker.
+fn relay(
+  head: &Head,
+  channel: &Channel<StreamMessage>,
+  read_body: impl FnOnce(&mut dyn FnMut(&[u8]) -> io::Result<()>) -> io::Result<()>,
+) -> io::Result<()> {
+  let send = |message| channel.send(message).map_err(io::Error::other);
+  send(StreamMessage::Head {
+    status: head.status,
+    headers: head.forwarded_headers().cloned().collect(),
+  })?;
+
+  // Holds the start of a UTF-8 sequence split across two chunks.
+  let mut pending = Vec::new();
+  read_body(&mut |chunk: &[u8]| {
+    pending.extend_from_slice(chunk);
+    let complete = match std::str::from_utf8(&pending) {
+      Ok(text) => text.len(),
+      Err(err) if err.error_len().is_none() => err.valid_up_to(),
+      // Genuinely invalid; let the lossy conversion replace it.
+      Err(_) => pending.len(),
+    };
+    if complete == 0 {
+      return Ok(());
+    }
+    let data = String::from_utf8_lossy(&pending[..complete]).into_owned();
+    pending.drain(..complete);
+    send(StreamMessage::Chunk { data })
+  })?;
+  if !pending.is_empty() {
+    send(StreamMessage::Chunk {
+      data: String::from_utf8_lossy(&pending).into_owned(),
+    })?;
+  }
+  send(StreamMessage::End)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Cursor;
+
+  fn headers(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+      .iter()
+      .map(|(name, value)| (name.to_string(), value.to_string()))
+      .collect()
+  }
+
+  fn body_of(raw: &str) -> io::Result<Vec<u8>> {
+    let mut reader = Cursor::new(raw.as_bytes().to_vec());
+    let head = read_head(&mut reader)?;
+    let mut body = Vec::new();
+    read_body(&mut reader, &head, |chunk| {
+      body.extend_from_slice(chunk);
+      Ok(())
+    })?;
+    Ok(body)
+  }
+
+  #[test]
+  fn only_api_paths_pass() {
+    assert!(check_path("/api/threads?limit=5").is_ok());
+    assert!(check_path("/").is_err());
+    assert!(check_path("/apix").is_err());
+    assert!(check_path("/api/x HTTP/1.1\r\nHost: evil").is_err());
+    assert!(check_path("/api/a\tb").is_err());
+  }
+
+  #[test]
+  fn request_parts_with_line_breaks_are_rejected() {
+    let none = headers(&[]);
+    assert!(parse_request("GET /api/x HTTP/1.1\r\n", "/api/y", &none).is_err());
+    assert!(parse_request("PO ST", "/api/y", &none).is_err());
+    let smuggled = headers(&[("X-Test", "a\r\nAuthorization: Bearer other")]);
+    assert!(parse_request("POST", "/api/y", &smuggled).is_err());
+    let bad_name = headers(&[("X-Test\r\nAuthorization", "a")]);
+    assert!(parse_request("POST", "/api/y", &bad_name).is_err());
+    let fine = headers(&[("Accept", "text/event-stream")]);
+    let (method, parsed) = parse_request("POST", "/api/y", &fine).unwrap();
+    assert_eq!(method, Method::POST);
+    assert_eq!(parsed.get(header::ACCEPT).unwrap(), "text/event-stream");
+  }
+
+  #[test]
+  fn write_request_drops_headers_the_host_owns() {
+    let (method, parsed) = parse_request(
+      "POST",
+      "/api/chat",
+      &headers(&[
+        ("Authorization", "Bearer from-webview"),
+        ("Content-Length", "999"),
+        ("Accept", "text/event-stream"),
+      ]),
+    )
+    .unwrap();
+    let mut written = Vec::new();
+    write_request(&mut written, &method, "/api/chat", &parsed, b"{}", "secret").unwrap();
+    assert_eq!(
+      String::from_utf8(written).unwrap(),
+      "POST /api/chat HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+       Authorization: Bearer secret\r\nContent-Length: 2\r\n\
+       accept: text/event-stream\r\n\r\n{}"
+    );
+  }
+
+  #[test]
+  fn chunked_bodies_are_decoded() {
+    let raw = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+               5\r\nhello\r\n7;ext=1\r\n, world\r\n0\r\n\r\n";
+    assert_eq!(body_of(raw).unwrap(), b"hello, world");
+  }
+
+  #[test]
+  fn chunk_sizes_are_hex() {
+    let chunk = "x".repeat(26);
+    let raw = format!(
+      "HTTP/1.1 200 OK\r\ntransfer-encoding: Chunked\r\n\r\n1A\r\n{chunk}\r\n0\r\n\r\n"
+    );
+    assert_eq!(body_of(&raw).unwrap(), chunk.as_bytes());
+  }
+
+  #[test]
+  fn broken_chunked_bodies_are_errors() {
+    let bad_size = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\nhello\r\n";
+    assert_eq!(body_of(bad_size).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    let truncated = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n10\r\nshort";
+    assert_eq!(body_of(truncated).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    let unterminated = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n";
+    assert_eq!(body_of(unterminated).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+  }
+
+  #[test]
+  fn content_length_bounds_the_body() {
+    let raw = "HTTP/1.1 404 Not Found\r\nContent-Length: 4\r\n\r\nnopeEXTRA";
+    assert_eq!(body_of(raw).unwrap(), b"nope");
+    let short = "HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nnope";
+    assert_eq!(body_of(short).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    let until_close = "HTTP/1.1 200 OK\r\n\r\nall of it";
+    assert_eq!(body_of(until_close).unwrap(), b"all of it");
+  }
+}
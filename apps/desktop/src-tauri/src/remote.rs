@@ -0,0 +1,409 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::{config, paths};
+
+const PROFILES_FILE: &str = "remote.json";
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+// Used with `--remote`, since a token on the command line would show up in `ps`.
+const TOKEN_ENV: &str = "PRO_CHAT_REMOTE_TOKEN";
+
+/// A saved API server the desktop shell can use instead of its own sidecar.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RemoteProfile {
+  pub url: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub token: Option<String>,
+}
+
+/// `remote.json` in the app data directory: the named profiles and the one
+/// used at launch (`None` means the built-in server).
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProfileStore {
+  #[serde(default)]
+  active: Option<String>,
+  #[serde(default)]
+  profiles: BTreeMap<String, RemoteProfile>,
+}
+
+impl ProfileStore {
+  fn load(data_dir: &Path) -> Self {
+    let path = data_dir.join(PROFILES_FILE);
+    let Ok(contents) = fs::read_to_string(&path) else {
+      return ProfileStore::default();
+    };
+    serde_json::from_str(&contents).unwrap_or_else(|err| {
+      log::warn!("Ignoring unreadable {}: {err}", path.display());
+      ProfileStore::default()
+    })
+  }
+
+  /// Written via a temporary file so a crash can't leave half a profile list.
+  /// The file holds access tokens, so it is only readable by the owner.
+  fn save(&self, data_dir: &Path) -> io::Result<()> {
+    let json = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+    paths::write_private(&data_dir.join(PROFILES_FILE), &json)
+  }
+
+  /// The token saved with profile `name`, but only while the profile still
+  /// points at `url`, so a token never goes to a server it wasn't entered for.
+  fn saved_token(&self, name: &str, url: &str) -> Option<String> {
+    let profile = self.profiles.get(name)?;
+    let same_url = profile.url.trim_end_matches('/') == url.trim_end_matches('/');
+    profile.token.clone().filter(|_| same_url)
+  }
+
+  /// Adds or replaces profile `name`; `token` as in `save_remote_profile`.
+  fn upsert(&mut self, name: String, url: String, token: Option<String>) {
+    let token = match token {
+      Some(token) => Some(token).filter(|token| !token.is_empty()),
+      None => self.saved_token(&name, &url),
+    };
+    self.profiles.insert(name, RemoteProfile { url, token });
+  }
+}
+
+/// The remote API this launch talks to, if any. Managed as Tauri state; a
+/// different profile takes effect on the next launch.
+pub struct RemoteBackend {
+  profile: Option<String>,
+  target: Option<RemoteProfile>,
+}
+
+impl RemoteBackend {
+  /// Picks the backend from the command line, falling back to the saved
+  /// active profile:
+  ///
+  /// - `--remote <url>` connects to `url` (token from `PRO_CHAT_REMOTE_TOKEN`)
+  /// - `--profile <name>` uses a saved profile for this launch only
+  /// - `--local` runs the built-in server regardless of the saved setting
+  pub fn resolve(data_dir: &Path, args: impl IntoIterator<Item = String>) -> Self {
+    let local = RemoteBackend {
+      profile: None,
+      target: None,
+    };
+    let mut remote_url = None;
+    let mut profile = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+      if arg == "--local" {
+        return local;
+      } else if let Some(url) = flag_value(&arg, "--remote", &mut args) {
+        remote_url = Some(url);
+      } else if let Some(name) = flag_value(&arg, "--profile", &mut args) {
+        profile = Some(name);
+      }
+    }
+
+    if let Some(url) = remote_url {
+      return match config::http_url(&url) {
+        Some(url) => RemoteBackend {
+          profile: None,
+          target: Some(RemoteProfile {
+            url,
+            token: std::env::var(TOKEN_ENV).ok().filter(|token| !token.is_empty()),
+          }),
+        },
+        None => {
+          log::warn!("Ignoring --remote {url}: expected an http:// or https:// URL");
+          local
+        }
+      };
+    }
+
+    let mut store = ProfileStore::load(data_dir);
+    let Some(name) = profile.or(store.active.take()) else {
+      return local;
+    };
+    match store.profiles.remove(&name) {
+      Some(target) => RemoteBackend {
+        profile: Some(name),
+        target: Some(target),
+      },
+      None => {
+        log::warn!("Remote profile \"{name}\" does not exist; using the built-in server");
+        local
+      }
+    }
+  }
+
+  pub fn target(&self) -> Option<&RemoteProfile> {
+    self.target.as_ref()
+  }
+}
+
+/// Accepts `--name value` and `--name=value`.
+fn flag_value(arg: &str, name: &str, rest: &mut impl Iterator<Item = String>) -> Option<String> {
+  if arg == name {
+    return rest.next();
+  }
+  arg.strip_prefix(name)?.strip_prefix('=').map(str::to_string)
+}
+
+/// Confirms `GET <url>/api/health` answers 2xx with the given token.
+pub async fn check(url: &str, token: Option<&str>) -> Result<(), String> {
+  let client = reqwest::Client::builder()
+    .timeout(CHECK_TIMEOUT)
+    .build()
+    .map_err(|err| err.to_string())?;
+  let mut request = client.get(format!("{url}/api/health"));
+  if let Some(token) = token {
+    request = request.bearer_auth(token);
+  }
+  let response = request.send().await.map_err(|err| err.to_string())?;
+  match response.status() {
+    status if status.is_success() => Ok(()),
+    StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+      Err("the server rejected the access token".to_string())
+    }
+    status => Err(format!("the server answered {status}")),
+  }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSummary {
+  name: String,
+  url: String,
+  has_token: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteSettings {
+  /// Profile used at the next launch.
+  active: Option<String>,
+  /// Profile (or `--remote` URL) this launch is connected to.
+  current_profile: Option<String>,
+  current_url: Option<String>,
+  profiles: Vec<ProfileSummary>,
+}
+
+fn data_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+  paths::app_data_dir(app).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub fn remote_settings(
+  app: AppHandle,
+  backend: State<'_, RemoteBackend>,
+) -> Result<RemoteSettings, String> {
+  let store = ProfileStore::load(&data_dir(&app)?);
+  Ok(RemoteSettings {
+    active: store.active,
+    current_profile: backend.profile.clone(),
+    current_url: backend.target.as_ref().map(|target| target.url.clone()),
+    profiles: store
+      .profiles
+      .into_iter()
+      .map(|(name, profile)| ProfileSummary {
+        name,
+        url: profile.url,
+        has_token: profile.token.is_some(),
+      })
+      .collect(),
+  })
+}
+
+/// Creates or updates a profile. A `None` token keeps the saved one as long as
+/// the URL is unchanged; an empty token removes it.
+#[tauri::command]
+pub fn save_remote_profile(
+  app: AppHandle,
+  name: String,
+  url: String,
+  token: Option<String>,
+) -> Result<(), String> {
+  let name = name.trim().to_string();
+  if name.is_empty() {
+    return Err("Profile name is required.".to_string());
+  }
+  let url = config::http_url(&url).ok_or(config::URL_ERROR)?;
+  let dir = data_dir(&app)?;
+  let mut store = ProfileStore::load(&dir);
+  store.upsert(name, url, token);
+  store.save(&dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub fn delete_remote_profile(app: AppHandle, name: String) -> Result<(), String> {
+  let dir = data_dir(&app)?;
+  let mut store = ProfileStore::load(&dir);
+  store.profiles.remove(&name);
+  if store.active.as_deref() == Some(name.as_str()) {
+    store.active = None;
+  }
+  store.save(&dir).map_err(|err| err.to_string())
+}
+
+/// Chooses the profile used from the next launch on; `None` selects the
+/// built-in server.
+#[tauri::command]
+pub fn set_active_remote_profile(app: AppHandle, name: Option<String>) -> Result<(), String> {
+  let dir = data_dir(&app)?;
+  let mut store = ProfileStore::load(&dir);
+  if let Some(name) = &name {
+    if !store.profiles.contains_key(name) {
+      return Err(format!("Remote profile \"{name}\" does not exist."));
+    }
+  }
+  store.active = name;
+  store.save(&dir).map_err(|err| err.to_string())
+}
+
+/// "Test connection" in settings. Without a token, a saved profile's token is
+/// used so it doesn't have to be re-entered, if the URL is the profile's.
+#[tauri::command]
+pub async fn check_remote(
+  app: AppHandle,
+  url: String,
+  token: Option<String>,
+  profile: Option<String>,
+) -> Result<(), String> {
+  let url = config::http_url(&url).ok_or(config::URL_ERROR)?;
+  let token = match token.filter(|token| !token.is_empty()) {
+    Some(token) => Some(token),
+    None => profile.and_then(|name| check_token(&data_dir(&app).ok()?, &name, &url)),
+  };
+  check(&url, token.as_deref()).await
+}
+
+fn check_token(data_dir: &Path, profile: &str, url: &str) -> Option<String> {
+  ProfileStore::load(data_dir).saved_token(profile, url)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::TempDir;
+
+  fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|arg| arg.to_string()).collect()
+  }
+
+  fn save_profiles(dir: &Path, active: Option<&str>) {
+    let mut store = ProfileStore {
+      active: active.map(str::to_string),
+      ..ProfileStore::default()
+    };
+    for (name, url) in [("home", "https://home.example"), ("work", "http://10.0.0.2:8787")] {
+      let profile = RemoteProfile {
+        url: url.to_string(),
+        token: Some(format!("{name}-token")),
+      };
+      store.profiles.insert(name.to_string(), profile);
+    }
+    store.save(dir).unwrap();
+  }
+
+  fn resolved_url(dir: &Path, list: &[&str]) -> Option<String> {
+    RemoteBackend::resolve(dir, args(list))
+      .target()
+      .map(|target| target.url.clone())
+  }
+
+  #[test]
+  fn flag_value_accepts_both_forms() {
+    let mut rest = args(&["https://a.example", "--local"]).into_iter();
+    assert_eq!(
+      flag_value("--remote", "--remote", &mut rest).as_deref(),
+      Some("https://a.example")
+    );
+    assert_eq!(rest.next().as_deref(), Some("--local"));
+
+    let mut empty = std::iter::empty();
+    assert_eq!(
+      flag_value("--remote=https://b.example", "--remote", &mut empty).as_deref(),
+      Some("https://b.example")
+    );
+    assert_eq!(flag_value("--remote", "--remote", &mut empty), None);
+    assert_eq!(flag_value("--remotes=x", "--remote", &mut empty), None);
+    assert_eq!(flag_value("--profile", "--remote", &mut empty), None);
+  }
+
+  #[test]
+  fn resolve_without_profiles_is_local() {
+    let dir = TempDir::new();
+    assert_eq!(resolved_url(dir.path(), &[]), None);
+  }
+
+  #[test]
+  fn resolve_uses_the_active_profile() {
+    let dir = TempDir::new();
+    save_profiles(dir.path(), Some("home"));
+    let backend = RemoteBackend::resolve(dir.path(), args(&[]));
+    assert_eq!(backend.profile.as_deref(), Some("home"));
+    let target = backend.target().unwrap();
+    assert_eq!(target.url, "https://home.example");
+    assert_eq!(target.token.as_deref(), Some("home-token"));
+  }
+
+  #[test]
+  fn resolve_prefers_the_command_line() {
+    let dir = TempDir::new();
+    save_profiles(dir.path(), Some("home"));
+    assert_eq!(
+      resolved_url(dir.path(), &["--profile", "work"]).as_deref(),
+      Some("http://10.0.0.2:8787")
+    );
+    assert_eq!(
+      resolved_url(dir.path(), &["--remote=https://other.example/"]).as_deref(),
+      Some("https://other.example")
+    );
+    assert_eq!(resolved_url(dir.path(), &["--profile=work", "--local"]), None);
+  }
+
+  #[test]
+  fn resolve_falls_back_to_local_on_bad_input() {
+    let dir = TempDir::new();
+    save_profiles(dir.path(), Some("home"));
+    assert_eq!(resolved_url(dir.path(), &["--remote", "ftp://x.example"]), None);
+    assert_eq!(resolved_url(dir.path(), &["--profile", "missing"]), None);
+  }
+
+  #[test]
+  fn saved_token_stays_with_its_url() {
+    let tmp = TempDir::new();
+    save_profiles(tmp.path(), None);
+    let store = ProfileStore::load(tmp.path());
+    assert_eq!(
+      store.saved_token("home", "https://home.example/").as_deref(),
+      Some("home-token")
+    );
+    assert_eq!(store.saved_token("home", "https://evil.example"), None);
+    assert_eq!(store.saved_token("missing", "https://home.example"), None);
+  }
+
+  #[test]
+  fn changed_url_drops_the_token() {
+    let tmp = TempDir::new();
+    save_profiles(tmp.path(), None);
+    let mut store = ProfileStore::load(tmp.path());
+    let token = |store: &ProfileStore, name: &str| store.profiles[name].token.clone();
+
+    store.upsert("home".to_string(), "https://home.example".to_string(), None);
+    assert_eq!(token(&store, "home").as_deref(), Some("home-token"));
+    store.upsert("work".to_string(), "https://evil.example".to_string(), None);
+    assert_eq!(token(&store, "work"), None);
+    store.upsert("home".to_string(), "https://home.example".to_string(), Some(String::new()));
+    assert_eq!(token(&store, "home"), None);
+  }
+
+  #[test]
+  fn check_against_another_url_sends_no_token() {
+    let tmp = TempDir::new();
+    save_profiles(tmp.path(), None);
+    assert_eq!(
+      check_token(tmp.path(), "work", "http://10.0.0.2:8787").as_deref(),
+      Some("work-token")
+    );
+    assert_eq!(check_token(tmp.path(), "work", "https://evil.example"), None);
+  }
+}
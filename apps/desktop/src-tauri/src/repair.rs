@@ -0,0 +1,556 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rusqlite::types::Value;
+use rusqlite::{Connection, ErrorCode, Row};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::backup::{self, BACKUP_DIR, TIMESTAMP_FORMAT};
+use crate::db;
+use crate::error::SidecarError;
+use crate::sidecar::{self, ApiProcess};
+use crate::{config, migrate, paths, snapshot};
+
+// Damaged databases are moved into `damaged/<timestamp>/` rather than deleted.
+const DAMAGED_DIR: &str = "damaged";
+const SALVAGE_FILE: &str = "pro-chat.db.salvage";
+const REPORT_FILE: &str = "salvage-report.json";
+// Snapshots are downloaded here before they are restored.
+const INCOMING_DIR: &str = "snapshot-incoming";
+
+/// Runs before the sidecar starts, so a damaged database shows up as a clear
+/// startup error instead of API 500s. `quick_check` by default; the full
+/// `integrity_check` when `[database] full_integrity_check` is set.
+pub fn check(app: &AppHandle) -> Result<(), SidecarError> {
+  let data_dir = paths::app_data_dir(app).map_err(SidecarError::DataDirUnavailable)?;
+  let path = data_dir.join(db::DB_FILE);
+  if !path.exists() {
+    return Ok(());
+  }
+  let full = config::load(&data_dir)?
+    .database
+    .full_integrity_check
+    .unwrap_or(false);
+  let result = if full {
+    db::integrity_check(&path)
+  } else {
+    db::quick_check(&path)
+  };
+  let problems = match result {
+    Ok(problems) => problems,
+    Err(err) if is_damage(&err) => vec![err.to_string()],
+    // A busy or locked database says nothing about its contents.
+    Err(err) => {
+      log::warn!("Could not check {}: {err}", path.display());
+      return Ok(());
+    }
+  };
+  if problems.is_empty() {
+    return Ok(());
+  }
+  for problem in &problems {
+    log::error!("Database check: {problem}");
+  }
+  Err(SidecarError::DatabaseCorrupt { path, problems })
+}
+
+fn is_damage(err: &rusqlite::Error) -> bool {
+  matches!(
+    err.sqlite_error_code(),
+    Some(ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase)
+  )
+}
+
+/// What a salvage got back, table by table.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SalvageReport {
+  pub tables: Vec<TableReport>,
+  /// Where the damaged database was moved.
+  pub damaged: PathBuf,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableReport {
+  pub table: String,
+  pub recovered: u64,
+  /// Rows that could not be read back. Past a damaged page the gap can only
+  /// be measured in row ids, so this is an upper bound.
+  pub unreadable: u64,
+  /// Readable rows dropped because the row they belonged to was lost.
+  pub orphaned: u64,
+  /// Set when the table could not be read at all.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+/// Copies every row that can still be read into a database freshly created
+/// from the migrations, drops rows whose parent didn't make it, then swaps the
+/// copy in and moves the damaged files aside.
+fn salvage(data_dir: &Path, migrations: &Path) -> io::Result<SalvageReport> {
+  let db_path = data_dir.join(db::DB_FILE);
+  let salvage_path = data_dir.join(SALVAGE_FILE);
+  let _ = fs::remove_file(&salvage_path);
+  migrate::run(&salvage_path, migrations).map_err(|err| io::Error::other(err.to_string()))?;
+
+  let mut tables = {
+    let old = db::open_read_only(&db_path).map_err(io::Error::other)?;
+    let mut new = Connection::open(&salvage_path).map_err(io::Error::other)?;
+    let tx = new.transaction().map_err(io::Error::other)?;
+    let names: Vec<String> = {
+      let mut stmt = tx
+        .prepare(
+          "SELECT name FROM sqlite_master WHERE type = 'table' \
+           AND name NOT LIKE 'sqlite_%' AND name != '_prisma_migrations' ORDER BY name",
+        )
+        .map_err(io::Error::other)?;
+      let rows = stmt.query_map([], |row| row.get(0)).map_err(io::Error::other)?;
+      rows.collect::<rusqlite::Result<_>>().map_err(io::Error::other)?
+    };
+    let mut tables: Vec<TableReport> = names
+      .iter()
+      .map(|table| copy_table(&old, &tx, table))
+      .collect();
+    for (table, dropped) in drop_orphans(&tx).map_err(io::Error::other)? {
+      if let Some(report) = tables.iter_mut().find(|report| report.table == table) {
+        report.orphaned = dropped;
+        report.recovered = report.recovered.saturating_sub(dropped);
+      }
+    }
+    tx.commit().map_err(io::Error::other)?;
+    tables
+  };
+  tables.retain(|report| {
+    report.recovered > 0 || report.unreadable > 0 || report.orphaned > 0 || report.error.is_some()
+  });
+
+  let damaged = set_aside(data_dir)?;
+  if let Err(err) = fs::rename(&salvage_path, &db_path) {
+    put_back(&damaged, data_dir);
+    return Err(err);
+  }
+  let report = SalvageReport { tables, damaged };
+  let written = serde_json::to_vec_pretty(&report)
+    .map_err(io::Error::other)
+    .and_then(|json| fs::write(report.damaged.join(REPORT_FILE), json));
+  if let Err(err) = written {
+    log::warn!("Failed to save the salvage report: {err}");
+  }
+  Ok(report)
+}
+
+/// Copies `table` row by row, keeping row ids. A read error ends a scan, so
+/// the copy steps over one row id and scans again from there until it passes
+/// the highest row id the table reports.
+fn copy_table(old: &Connection, new: &Connection, table: &str) -> TableReport {
+  let mut report = TableReport {
+    table: table.to_string(),
+    ..TableReport::default()
+  };
+  let columns = |conn: &Connection| -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info(?1)")?;
+    let rows = stmt.query_map([table], |row| row.get(0))?;
+    rows.collect()
+  };
+  let (wanted, present) = match (columns(new), columns(old)) {
+    (Ok(wanted), Ok(present)) => (wanted, present),
+    (Err(err), _) | (_, Err(err)) => {
+      report.error = Some(err.to_string());
+      return report;
+    }
+  };
+  // Columns the damaged copy lacks keep their defaults.
+  let shared: Vec<&String> = wanted.iter().filter(|name| present.contains(name)).collect();
+  if shared.is_empty() {
+    report.error = Some("the table is missing from the damaged database".to_string());
+    return report;
+  }
+  let list = shared
+    .iter()
+    .map(|name| format!("\"{name}\""))
+    .collect::<Vec<_>>()
+    .join(", ");
+  let placeholders = (2..=shared.len() + 1)
+    .map(|n| format!("?{n}"))
+    .collect::<Vec<_>>()
+    .join(", ");
+  let scan = format!("SELECT rowid, {list} FROM \"{table}\" WHERE rowid > ?1 ORDER BY rowid");
+  let probe = format!("SELECT rowid, {list} FROM \"{table}\" WHERE rowid = ?1");
+  let insert =
+    format!("INSERT OR IGNORE INTO \"{table}\" (rowid, {list}) VALUES (?1, {placeholders})");
+
+  let max_rowid: Option<i64> = old
+    .query_row(&format!("SELECT max(rowid) FROM \"{table}\""), [], |row| row.get(0))
+    .ok()
+    .flatten();
+  // False when the row is rejected by a constraint, which a damaged value
+  // can trip.
+  let insert_row = |values: Vec<Value>| {
+    new
+      .prepare_cached(&insert)
+      .and_then(|mut stmt| stmt.execute(rusqlite::params_from_iter(values)))
+      .is_ok_and(|inserted| inserted == 1)
+  };
+  // Prisma's tables only ever get positive row ids.
+  let mut last = 0i64;
+  loop {
+    let scanned = scan_from(old, &scan, &mut last, shared.len(), &insert_row, &mut report);
+    let Err(err) = scanned else {
+      break;
+    };
+    let Some(max) = max_rowid.filter(|max| last < *max) else {
+      log::warn!("Salvage of {table} stopped after row {last}: {err}");
+      report.unreadable += 1;
+      break;
+    };
+    last += 1;
+    match old.query_row(&probe, [last], |row| row_values(row, shared.len())) {
+      Ok(values) if insert_row(values) => report.recovered += 1,
+      Err(rusqlite::Error::QueryReturnedNoRows) => {}
+      _ => report.unreadable += 1,
+    }
+    if last >= max {
+      break;
+    }
+  }
+  report
+}
+
+/// Copies every row after `last`, advancing `last` as it goes.
+fn scan_from(
+  old: &Connection,
+  sql: &str,
+  last: &mut i64,
+  columns: usize,
+  insert_row: &impl Fn(Vec<Value>) -> bool,
+  report: &mut TableReport,
+) -> rusqlite::Result<()> {
+  let mut stmt = old.prepare(sql)?;
+  let mut rows = stmt.query([*last])?;
+  while let Some(row) = rows.next()? {
+    let values = row_values(row, columns)?;
+    if let Value::Integer(rowid) = values[0] {
+      *last = rowid;
+    }
+    if insert_row(values) {
+      report.recovered += 1;
+    } else {
+      report.unreadable += 1;
+    }
+  }
+  Ok(())
+}
+
+fn row_values(row: &Row<'_>, columns: usize) -> rusqlite::Result<Vec<Value>> {
+  (0..=columns).map(|index| row.get::<_, Value>(index)).collect()
+}
+
+/// Resolves every foreign key left dangling by lost rows the way the schema
+/// says a deleted parent should: `SET NULL` keys are cleared, anything else
+/// loses the row. Repeats until nothing dangles. Returns rows removed per table.
+fn drop_orphans(conn: &Connection) -> rusqlite::Result<BTreeMap<String, u64>> {
+  let mut dropped = BTreeMap::new();
+  loop {
+    let violations: Vec<(String, i64, i64)> = {
+      let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+      let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(3)?)))?;
+      rows.collect::<rusqlite::Result<_>>()?
+    };
+    if violations.is_empty() {
+      return Ok(dropped);
+    }
+    let mut changed = 0;
+    for (table, rowid, key) in violations {
+      let (column, on_delete): (String, String) = conn.query_row(
+        "SELECT \"from\", on_delete FROM pragma_foreign_key_list(?1) WHERE id = ?2",
+        rusqlite::params![table, key],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+      )?;
+      if on_delete == "SET NULL" {
+        changed += conn.execute(
+          &format!("UPDATE \"{table}\" SET \"{column}\" = NULL WHERE rowid = ?1"),
+          [rowid],
+        )?;
+      } else if conn.execute(&format!("DELETE FROM \"{table}\" WHERE rowid = ?1"), [rowid])? > 0 {
+        changed += 1;
+        *dropped.entry(table).or_insert(0) += 1;
+      }
+    }
+    // Guards against looping forever on a violation neither fix touches.
+    if changed == 0 {
+      return Ok(dropped);
+    }
+  }
+}
+
+/// Moves the database and its journal files into a new `damaged/<timestamp>`
+/// directory and returns it.
+fn set_aside(data_dir: &Path) -> io::Result<PathBuf> {
+  let stamp = chrono::Local::now().format(TIMESTAMP_FORMAT).to_string();
+  let dir = data_dir.join(DAMAGED_DIR).join(stamp);
+  fs::create_dir_all(&dir)?;
+  for name in db::database_files() {
+    match fs::rename(data_dir.join(&name), dir.join(&name)) {
+      Ok(()) => {}
+      Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+      Err(err) => {
+        put_back(&dir, data_dir);
+        return Err(err);
+      }
+    }
+  }
+  log::info!("Moved the damaged database to {}", dir.display());
+  Ok(dir)
+}
+
+fn put_back(damaged: &Path, data_dir: &Path) {
+  for name in db::database_files() {
+    let _ = fs::rename(damaged.join(&name), data_dir.join(&name));
+  }
+}
+
+/// The newest archive to restore from: a local backup, or a snapshot at the
+/// configured destination, downloaded into `incoming` if it is the newer one.
+fn latest_archive(data_dir: &Path, incoming: &Path) -> io::Result<Option<PathBuf>> {
+  let local = fs::read_dir(data_dir.join(BACKUP_DIR))
+    .map(|entries| {
+      entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+          let name = path.file_name()?.to_str()?;
+          Some((backup::archive_time(name)?, path))
+        })
+        .max()
+    })
+    .ok()
+    .flatten();
+  let remote = snapshot::newest(data_dir).unwrap_or_else(|err| {
+    log::warn!("Could not list snapshots: {err}");
+    None
+  });
+  let remote_is_newer = match (&local, &remote) {
+    (_, None) => false,
+    (None, Some(_)) => true,
+    (Some((time, _)), Some((_, taken))) => taken > time,
+  };
+  match remote {
+    Some((name, _)) if remote_is_newer => {
+      fs::create_dir_all(incoming)?;
+      let path = incoming.join(&name);
+      log::info!("Downloading snapshot {name}");
+      snapshot::download(data_dir, &name, &path)?;
+      Ok(Some(path))
+    }
+    _ => Ok(local.map(|(_, path)| path)),
+  }
+}
+
+fn stop_sidecar(app: &AppHandle) {
+  if let Some(process) = app.try_state::<ApiProcess>() {
+    process.stop();
+  }
+}
+
+/// Restores the newest backup or snapshot over the damaged database, which is
+/// moved aside first and put back if the restore fails.
+fn restore_latest(app: &AppHandle, data_dir: &Path, incoming: &Path) -> Result<String, String> {
+  let archive = latest_archive(data_dir, incoming)
+    .map_err(|err| format!("Could not fetch the latest snapshot: {err}"))?
+    .ok_or("There is no backup or snapshot to restore from.")?;
+  let damaged = set_aside(data_dir).map_err(|err| err.to_string())?;
+  match backup::restore(app, data_dir, &archive) {
+    Ok(manifest) => Ok(format!(
+      "Restored the backup taken {} by pro-chat {}. Anything written since then is not \
+       included; the damaged database was moved to {}.",
+      manifest.created_at,
+      manifest.app_version,
+      damaged.display()
+    )),
+    Err(err) => {
+      put_back(&damaged, data_dir);
+      Err(format!("Restore failed: {err}"))
+    }
+  }
+}
+
+/// "Restore latest backup" on the startup error screen.
+#[tauri::command]
+pub async fn restore_latest_backup(app: AppHandle) -> Result<String, String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    let data_dir = paths::app_data_dir(&app).map_err(|err| err.to_string())?;
+    stop_sidecar(&app);
+    let incoming = data_dir.join(INCOMING_DIR);
+    let result = restore_latest(&app, &data_dir, &incoming);
+    let _ = fs::remove_dir_all(&incoming);
+    if let Ok(message) = &result {
+      log::info!("{message}");
+    }
+    result
+  })
+  .await
+  .map_err(|err| err.to_string())?
+}
+
+/// "Salvage data" on the startup error screen.
+#[tauri::command]
+pub async fn salvage_database(app: AppHandle) -> Result<SalvageReport, String> {
+  tauri::async_runtime::spawn_blocking(move || -> Result<SalvageReport, String> {
+    let data_dir = paths::app_data_dir(&app).map_err(|err| err.to_string())?;
+    let mode = app
+      .try_state::<ApiProcess>()
+      .map(|process| process.mode())
+      .ok_or("The built-in server is not in use.")?;
+    let migrations = sidecar::migrations_dir(&app, mode).map_err(|err| err.to_string())?;
+    stop_sidecar(&app);
+    let report = salvage(&data_dir, &migrations).map_err(|err| format!("Salvage failed: {err}"))?;
+    for table in &report.tables {
+      log::info!(
+        "Salvaged {}: {} recovered, {} unreadable, {} orphaned",
+        table.table,
+        table.recovered,
+        table.unreadable,
+        table.orphaned
+      );
+    }
+    Ok(report)
+  })
+  .await
+  .map_err(|err| err.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::TempDir;
+
+  const SCHEMA: &str = r#"CREATE TABLE "Thread" ("id" TEXT NOT NULL PRIMARY KEY, "title" TEXT);
+CREATE TABLE "Message" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "threadId" TEXT NOT NULL,
+    "body" TEXT NOT NULL,
+    CONSTRAINT "Message_threadId_fkey" FOREIGN KEY ("threadId") REFERENCES "Thread" ("id")
+      ON DELETE CASCADE ON UPDATE CASCADE
+);
+CREATE TABLE "Attachment" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "messageId" TEXT,
+    CONSTRAINT "Attachment_messageId_fkey" FOREIGN KEY ("messageId") REFERENCES "Message" ("id")
+      ON DELETE SET NULL ON UPDATE CASCADE
+);
+CREATE TABLE "Empty" ("id" TEXT NOT NULL PRIMARY KEY);
+"#;
+  const MESSAGES: i64 = 60;
+  // Row ids never used, just ahead of the page that gets zeroed.
+  const GAP: [i64; 2] = [38, 39];
+  // Messages whose thread was deleted; the foreign key cascades.
+  const ORPHANS: [i64; 2] = [2, 3];
+
+  fn marker(rowid: i64) -> String {
+    format!("row-{rowid:04}-")
+  }
+
+  fn contains(haystack: &[u8], needle: &str) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle.as_bytes())
+  }
+
+  /// Writes a database with a deleted thread, a gap in the message row ids
+  /// and a zeroed page in the middle of the messages. Returns the row ids of
+  /// the messages that were on that page, in order.
+  fn damaged_database(path: &Path) -> Vec<i64> {
+    let conn = Connection::open(path).unwrap();
+    conn.execute_batch(SCHEMA).unwrap();
+    conn
+      .execute_batch(
+        "INSERT INTO Thread (id, title) VALUES ('kept', 'Kept'), ('gone', 'Gone');
+         DELETE FROM Thread WHERE id = 'gone';",
+      )
+      .unwrap();
+    for rowid in (1..=MESSAGES).filter(|rowid| !GAP.contains(rowid)) {
+      let thread = if ORPHANS.contains(&rowid) { "gone" } else { "kept" };
+      // Big enough that the table spans many pages.
+      let body = format!("{}{}", marker(rowid), "x".repeat(1000));
+      conn
+        .execute(
+          "INSERT INTO Message (rowid, id, threadId, body) VALUES (?1, ?2, ?3, ?4)",
+          rusqlite::params![rowid, format!("m{rowid}"), thread, body],
+        )
+        .unwrap();
+    }
+    conn
+      .execute_batch(
+        "INSERT INTO Attachment (id, messageId) VALUES ('a1', 'm2'), ('a2', 'm1'), ('a3', 'm40');",
+      )
+      .unwrap();
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0)).unwrap();
+    drop(conn);
+
+    let page_size = page_size as usize;
+    let mut bytes = fs::read(path).unwrap();
+    let needle = marker(40);
+    let offset = bytes
+      .windows(needle.len())
+      .position(|window| window == needle.as_bytes())
+      .unwrap();
+    let page = offset / page_size * page_size..(offset / page_size + 1) * page_size;
+    let lost = (1..=MESSAGES)
+      .filter(|rowid| contains(&bytes[page.clone()], &marker(*rowid)))
+      .collect();
+    bytes[page].fill(0);
+    fs::write(path, bytes).unwrap();
+    lost
+  }
+
+  fn column<T: rusqlite::types::FromSql>(conn: &Connection, sql: &str) -> Vec<T> {
+    let mut stmt = conn.prepare(sql).unwrap();
+    let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+    rows.collect::<rusqlite::Result<_>>().unwrap()
+  }
+
+  #[test]
+  fn salvage_copies_what_is_readable_and_resolves_orphans() {
+    let tmp = TempDir::new();
+    let migrations = tmp.path().join("migrations");
+    fs::create_dir_all(migrations.join("20260101000000_init")).unwrap();
+    fs::write(migrations.join("20260101000000_init/migration.sql"), SCHEMA).unwrap();
+    let lost = damaged_database(&tmp.path().join(db::DB_FILE));
+    assert!(lost.contains(&40) && lost.iter().all(|rowid| !ORPHANS.contains(rowid)));
+
+    let report = salvage(tmp.path(), &migrations).unwrap();
+
+    let counts = |table: &str| {
+      let report = report.tables.iter().find(|report| report.table == table).unwrap();
+      assert_eq!(report.error, None, "{table}");
+      (report.recovered, report.unreadable, report.orphaned)
+    };
+    // Past the last row before the zeroed page, every row id up to the last
+    // one on it is probed and counted, the unused ones included.
+    let before = (1..lost[0]).rev().find(|rowid| !GAP.contains(rowid)).unwrap();
+    let stepped = (lost[lost.len() - 1] - before) as u64;
+    let readable = (MESSAGES as usize - GAP.len() - lost.len()) as u64;
+    assert_eq!(counts("Thread"), (1, 0, 0));
+    assert_eq!(counts("Message"), (readable - 2, stepped, 2));
+    assert_eq!(counts("Attachment"), (3, 0, 0));
+    assert_eq!(report.tables.len(), 3, "empty tables are left out of the report");
+
+    let conn = Connection::open(tmp.path().join(db::DB_FILE)).unwrap();
+    let expected: Vec<i64> = (1..=MESSAGES)
+      .filter(|rowid| !GAP.contains(rowid) && !lost.contains(rowid) && !ORPHANS.contains(rowid))
+      .collect();
+    assert_eq!(column::<i64>(&conn, "SELECT rowid FROM Message ORDER BY rowid"), expected);
+    assert_eq!(
+      column::<Option<String>>(&conn, "SELECT messageId FROM Attachment ORDER BY id"),
+      [None, Some("m1".to_string()), None],
+      "attachments of lost messages lose the link, not the row"
+    );
+    assert!(column::<String>(&conn, "PRAGMA foreign_key_check").is_empty());
+
+    assert!(report.damaged.join(db::DB_FILE).is_file());
+    assert!(report.damaged.join(REPORT_FILE).is_file());
+    assert!(!tmp.path().join(SALVAGE_FILE).exists());
+  }
+}
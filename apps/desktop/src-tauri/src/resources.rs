@@ -0,0 +1,169 @@
+use std::process::Command;
+
+use tauri::AppHandle;
+
+use crate::config::ResourceConfig;
+
+/// Applies the `[resources]` ceilings and nice level to the sidecar's command.
+/// Hard limits are lowered too, since Node raises its soft file limit to the
+/// hard one at startup. They are inherited by the Python tool runs as well.
+#[cfg(target_os = "linux")]
+pub fn apply(command: &mut Command, resources: &ResourceConfig) {
+  use std::io;
+  use std::os::unix::process::CommandExt;
+
+  const MIB: u64 = 1024 * 1024;
+  let requested = [
+    (libc::RLIMIT_AS, "address space", resources.max_address_space_mb.map(|mb| mb * MIB)),
+    (libc::RLIMIT_DATA, "data", resources.max_data_mb.map(|mb| mb * MIB)),
+    (libc::RLIMIT_NOFILE, "open files", resources.max_open_files),
+  ];
+  let mut limits = Vec::new();
+  for (resource, name, value) in requested {
+    let Some(value) = value else { continue };
+    let mut current = libc::rlimit {
+      rlim_cur: 0,
+      rlim_max: 0,
+    };
+    // SAFETY: getrlimit only writes to the struct we pass.
+    let hard = if unsafe { libc::getrlimit(resource, &mut current) } == 0 {
+      current.rlim_max
+    } else {
+      libc::RLIM_INFINITY
+    };
+    // Raising a hard limit needs privileges the host doesn't have.
+    let value = if hard != libc::RLIM_INFINITY && value > hard {
+      log::warn!("The {name} limit is capped at {hard} by the system; using that instead");
+      hard
+    } else {
+      value
+    };
+    limits.push((resource, value));
+  }
+  let nice = resources.nice;
+  if limits.is_empty() && nice.is_none() {
+    return;
+  }
+
+  // SAFETY: between fork and exec the closure only makes async-signal-safe
+  // system calls and allocates nothing.
+  unsafe {
+    command.pre_exec(move || {
+      for (resource, value) in &limits {
+        let limit = libc::rlimit {
+          rlim_cur: *value,
+          rlim_max: *value,
+        };
+        if libc::setrlimit(*resource, &limit) != 0 {
+          return Err(io::Error::last_os_error());
+        }
+      }
+      if let Some(nice) = nice {
+        if libc::setpriority(libc::PRIO_PROCESS, 0, nice) != 0 {
+          return Err(io::Error::last_os_error());
+        }
+      }
+      Ok(())
+    });
+  }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn apply(_command: &mut Command, resources: &ResourceConfig) {
+  if *resources != ResourceConfig::default() {
+    log::debug!("[resources] limits are only applied on Linux");
+  }
+}
+
+/// Restarts the sidecar once its process group's resident memory passes
+/// `resources.soft_rss_mb`, waiting until no stream is in progress so nobody
+/// loses a reply mid-way. The hard limits from `apply` are the backstop.
+#[cfg(target_os = "linux")]
+pub fn monitor(app: AppHandle) {
+  use std::thread;
+  use std::time::Duration;
+
+  use tauri::Manager;
+
+  use crate::sidecar::{ApiProcess, ApiState};
+  use crate::{config, paths, watchdog};
+
+  const INTERVAL: Duration = Duration::from_secs(10);
+
+  thread::spawn(move || {
+    let Ok(data_dir) = paths::app_data_dir(&app) else {
+      return;
+    };
+    // Set while a restart is waiting on streams, so that is logged once.
+    let mut deferred = false;
+    loop {
+      thread::sleep(INTERVAL);
+      let Some(threshold_mb) = config::load(&data_dir)
+        .ok()
+        .and_then(|config| config.resources.soft_rss_mb)
+      else {
+        deferred = false;
+        continue;
+      };
+      let Some(process) = app.try_state::<ApiProcess>() else {
+        continue;
+      };
+      let status = process.status();
+      let Some(pid) = status.pid.filter(|_| status.state == ApiState::Running) else {
+        deferred = false;
+        continue;
+      };
+      let rss_mb = group_rss_bytes(pid) / (1024 * 1024);
+      if rss_mb < threshold_mb {
+        deferred = false;
+        continue;
+      }
+
+      let live_streams = watchdog::live_streams(&app);
+      if live_streams > 0 {
+        if !deferred {
+          log::warn!(
+            "API server is using {rss_mb} MB (limit {threshold_mb} MB); \
+             restarting once {live_streams} stream(s) finish"
+          );
+          deferred = true;
+        }
+        continue;
+      }
+      log::warn!("API server is using {rss_mb} MB (limit {threshold_mb} MB); restarting it");
+      deferred = false;
+      if let Err(err) = process.start() {
+        log::error!("Failed to restart the API server: {err}");
+      }
+    }
+  });
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn monitor(_app: AppHandle) {}
+
+/// Sum of the resident set sizes of every process in the group `pgid` leads:
+/// Node plus any Python tool runs it started.
+#[cfg(target_os = "linux")]
+fn group_rss_bytes(pgid: u32) -> u64 {
+  use std::fs;
+
+  // SAFETY: sysconf has no preconditions.
+  let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(0) as u64;
+  let Ok(entries) = fs::read_dir("/proc") else {
+    return 0;
+  };
+  entries
+    .flatten()
+    .filter(|entry| entry.file_name().to_string_lossy().parse::<u32>().is_ok())
+    .filter_map(|entry| fs::read_to_string(entry.path().join("stat")).ok())
+    .filter_map(|stat| {
+      // The command name may contain spaces; the fields after it don't. Counting
+      // from the state field (3), the group id is field 5 and rss field 24.
+      let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+      let group: u32 = fields.get(2)?.parse().ok()?;
+      let pages: u64 = fields.get(21)?.parse().ok()?;
+      (group == pgid).then_some(pages * page_size)
+    })
+    .sum()
+}
@@ -0,0 +1,848 @@
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::Level;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::error::SidecarError;
+use crate::transport::Endpoint;
+use crate::{clock, config, db, integrity, logging, migrate, paths, pidfile, resources};
+
+pub const STATUS_EVENT: &str = "api://status";
+// Only meaningful in debug builds, where the checkout is still on disk.
+const API_SOURCE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../api");
+// The Vite dev server proxies `/api` here, so source mode prefers it.
+const DEV_PORT: u16 = 8787;
+const DEV_ORIGIN: &str = "http://localhost:5173";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+// A child that stays up this long resets the backoff to its initial value.
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+// Crash-loop limit: this many exits inside CRASH_WINDOW stops the supervisor.
+const MAX_CRASHES: usize = 5;
+const CRASH_WINDOW: Duration = Duration::from_secs(120);
+const MAX_RESTART_RECORDS: usize = 50;
+// How long the API gets to exit on SIGTERM before it is killed outright.
+pub const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// How the host runs the API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SidecarMode {
+  /// `api/dist/index.js` and `bin/node` from the app's resources.
+  Bundled,
+  /// `apps/api/src` through tsx, restarted whenever the source changes.
+  Source,
+  /// Nothing is spawned; the developer runs the API by hand.
+  External,
+}
+
+impl SidecarMode {
+  /// Release builds always run the bundle. Debug builds default to `Source`;
+  /// `PRO_CHAT_SIDECAR=bundled` exercises the packaged layout instead and
+  /// `PRO_CHAT_SIDECAR=external` restores the run-it-yourself workflow.
+  pub fn detect() -> Self {
+    if !cfg!(debug_assertions) {
+      return SidecarMode::Bundled;
+    }
+    match std::env::var("PRO_CHAT_SIDECAR").as_deref() {
+      Ok("bundled") => SidecarMode::Bundled,
+      Ok("external") => SidecarMode::External,
+      _ => SidecarMode::Source,
+    }
+  }
+
+  pub fn source_dir() -> PathBuf {
+    PathBuf::from(API_SOURCE_DIR)
+  }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartRecord {
+  pub exit_code: Option<i32>,
+  pub exited_at_ms: u64,
+  pub backoff_ms: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ApiState {
+  #[default]
+  Stopped,
+  Running,
+  /// Exited unexpectedly; waiting out the backoff before respawning.
+  Restarting,
+  /// Hit the crash-loop limit and stopped trying.
+  Failed,
+}
+
+/// Snapshot returned by `api_status` and emitted as `api://status`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiStatus {
+  pub state: ApiState,
+  pub pid: Option<u32>,
+  pub port: Option<u16>,
+  pub socket: Option<PathBuf>,
+  pub uptime_ms: Option<u64>,
+  pub restart_count: u64,
+  pub last_exit_code: Option<i32>,
+  pub restarts: Vec<RestartRecord>,
+}
+
+#[derive(Default)]
+pub struct Supervisor {
+  state: ApiState,
+  endpoint: Option<Endpoint>,
+  data_dir: Option<PathBuf>,
+  child: Option<Child>,
+  started_at: Option<Instant>,
+  restarts: VecDeque<RestartRecord>,
+  // `restarts` keeps only the latest records; this counts all of them.
+  restart_count: u64,
+  recent_crashes: VecDeque<Instant>,
+  consecutive_failures: u32,
+  // Bumped on every start and stop so a stale watcher thread knows to exit.
+  generation: u64,
+}
+
+impl Supervisor {
+  fn socket(&self) -> Option<PathBuf> {
+    self
+      .endpoint
+      .as_ref()
+      .and_then(Endpoint::socket)
+      .map(Path::to_path_buf)
+  }
+
+  fn status(&self) -> ApiStatus {
+    ApiStatus {
+      state: self.state,
+      pid: self.child.as_ref().map(Child::id),
+      port: self.endpoint.as_ref().and_then(Endpoint::port),
+      socket: self.socket(),
+      uptime_ms: self
+        .started_at
+        .map(|started| started.elapsed().as_millis() as u64),
+      restart_count: self.restart_count,
+      last_exit_code: self.restarts.back().and_then(|record| record.exit_code),
+      restarts: self.restarts.iter().cloned().collect(),
+    }
+  }
+}
+
+/// Managed for the lifetime of the app; the sidecar itself can be started and
+/// stopped any number of times underneath it.
+#[derive(Clone)]
+pub struct ApiProcess {
+  app: AppHandle,
+  mode: SidecarMode,
+  /// Per-launch secret the API requires on every `/api` request but health.
+  token: Arc<str>,
+  supervisor: Arc<Mutex<Supervisor>>,
+  /// Held for the whole of `start`, `stop` and a respawn after a crash. Many
+  /// things restart the sidecar (the watchdog, the config watcher, the UI, a
+  /// restore); without this two overlapping starts could each spawn a child
+  /// and lose track of one.
+  lifecycle: Arc<Mutex<()>>,
+}
+
+impl ApiProcess {
+  pub fn new(app: &AppHandle, mode: SidecarMode) -> Self {
+    ApiProcess {
+      app: app.clone(),
+      mode,
+      token: generate_token().into(),
+      supervisor: Arc::default(),
+      lifecycle: Arc::default(),
+    }
+  }
+
+  pub fn mode(&self) -> SidecarMode {
+    self.mode
+  }
+
+  fn lock(&self) -> MutexGuard<'_, Supervisor> {
+    self.supervisor.lock().unwrap_or_else(PoisonError::into_inner)
+  }
+
+  // Always taken before `lock`, never while holding it.
+  fn lock_lifecycle(&self) -> MutexGuard<'_, ()> {
+    self.lifecycle.lock().unwrap_or_else(PoisonError::into_inner)
+  }
+
+  /// Emits the current status as `api://status`.
+  fn publish(&self) {
+    let status = self.status();
+    let _ = self.app.emit(STATUS_EVENT, status);
+  }
+
+  pub fn status(&self) -> ApiStatus {
+    self.lock().status()
+  }
+
+  /// Spawns the sidecar, replacing any running one, and starts a watcher
+  /// thread that restarts it when it exits. Returns where it listens.
+  pub fn start(&self) -> Result<Endpoint, SidecarError> {
+    let _lifecycle = self.lock_lifecycle();
+    self.shut_down();
+    let data_dir = app_data_dir(&self.app)?;
+    pidfile::reap_stale(&data_dir);
+
+    // Spawning hashes the bundle, asks Node for its version and migrates the
+    // database, so it runs outside the supervisor lock and status queries
+    // stay answerable meanwhile. `lifecycle` keeps other starts out.
+    let previous = self.endpoint();
+    let endpoint = choose_endpoint(&data_dir, self.mode, previous.as_ref())?;
+    let child = spawn_api(&self.app, self.mode, &self.token, &endpoint)?;
+    let mut guard = self.lock();
+    guard.generation += 1;
+    guard.state = ApiState::Running;
+    guard.endpoint = Some(endpoint.clone());
+    guard.data_dir = Some(data_dir);
+    let leftover = guard.child.replace(child);
+    guard.started_at = Some(Instant::now());
+    guard.recent_crashes.clear();
+    guard.consecutive_failures = 0;
+    let generation = guard.generation;
+    drop(guard);
+    if let Some(leftover) = leftover {
+      log::warn!("Terminating API server {} that was still running", leftover.id());
+      terminate(leftover);
+    }
+    self.publish();
+
+    let watched = self.clone();
+    thread::spawn(move || watched.watch(generation));
+
+    Ok(endpoint)
+  }
+
+  pub fn endpoint(&self) -> Option<Endpoint> {
+    self.lock().endpoint.clone()
+  }
+
+  /// Base URL the webview should send API requests to: the loopback port, or
+  /// the `prochat-api://` protocol in front of the socket.
+  pub fn base_url(&self) -> Option<String> {
+    self.endpoint().map(|endpoint| match endpoint {
+      Endpoint::Tcp(port) => format!("http://127.0.0.1:{port}"),
+      #[cfg(unix)]
+      Endpoint::Unix(_) => crate::proxy::BASE_URL.to_string(),
+    })
+  }
+
+  pub fn token(&self) -> &str {
+    &self.token
+  }
+
+  /// Stops supervision and terminates the current child, if any. Safe to call
+  /// from several exit paths; only the first call has work to do.
+  pub fn stop(&self) {
+    let _lifecycle = self.lock_lifecycle();
+    self.shut_down();
+  }
+
+  fn shut_down(&self) {
+    let (child, data_dir, socket) = {
+      let mut guard = self.lock();
+      guard.generation += 1;
+      guard.started_at = None;
+      let was_stopped = guard.state == ApiState::Stopped;
+      guard.state = ApiState::Stopped;
+      if was_stopped && guard.child.is_none() {
+        return;
+      }
+      (guard.child.take(), guard.data_dir.clone(), guard.socket())
+    };
+    if let Some(child) = child {
+      terminate(child);
+    }
+    clear_records(data_dir.as_deref(), socket.as_deref());
+    self.publish();
+  }
+
+  fn watch(&self, generation: u64) {
+    loop {
+      thread::sleep(POLL_INTERVAL);
+
+      let backoff = {
+        let mut guard = self.lock();
+        if guard.generation != generation {
+          return;
+        }
+        let exit_code = match guard.child.as_mut().map(|child| child.try_wait()) {
+          Some(Ok(Some(status))) => status.code(),
+          Some(Ok(None)) => continue,
+          Some(Err(err)) => {
+            log::error!("Failed to poll API server: {err}");
+            continue;
+          }
+          // The previous respawn failed; count it as another crash.
+          None => None,
+        };
+        if let Some(child) = guard.child.take() {
+          kill_orphans(&child);
+        }
+        let (record, backoff) = record_exit(&mut guard, exit_code);
+        if backoff.is_none() {
+          // Nothing will restart it, so leave no stale record for the next launch.
+          clear_records(guard.data_dir.as_deref(), guard.socket().as_deref());
+        }
+        log::warn!(
+          "API server exited with code {:?} (restart #{})",
+          record.exit_code,
+          guard.restart_count
+        );
+        backoff
+      };
+      self.publish();
+
+      let Some(backoff) = backoff else {
+        log::error!(
+          "API server crashed {MAX_CRASHES} times within {}s; giving up",
+          CRASH_WINDOW.as_secs()
+        );
+        return;
+      };
+      log::info!("Restarting API server in {}ms", backoff.as_millis());
+      thread::sleep(backoff);
+
+      // Respawning counts as a start: a concurrent `stop` waits for it and
+      // then finds the new child to terminate.
+      let _lifecycle = self.lock_lifecycle();
+      let endpoint = {
+        let guard = self.lock();
+        match &guard.endpoint {
+          Some(endpoint) if guard.generation == generation => endpoint.clone(),
+          _ => return,
+        }
+      };
+      let spawned = spawn_api(&self.app, self.mode, &self.token, &endpoint);
+      let mut guard = self.lock();
+      match spawned {
+        Ok(child) => {
+          guard.child = Some(child);
+          guard.started_at = Some(Instant::now());
+          guard.state = ApiState::Running;
+        }
+        Err(err) => {
+          log::error!("Failed to restart API server: {err}");
+        }
+      }
+      drop(guard);
+      self.publish();
+    }
+  }
+}
+
+#[tauri::command]
+pub fn api_status(process: State<'_, ApiProcess>) -> ApiStatus {
+  process.status()
+}
+
+// Starting and stopping block for seconds, so both run off the async runtime.
+#[tauri::command]
+pub async fn restart_api(process: State<'_, ApiProcess>) -> Result<ApiStatus, String> {
+  log::info!("API restart requested from the UI");
+  let process = process.inner().clone();
+  tauri::async_runtime::spawn_blocking(move || {
+    process.start().map_err(|err| err.to_string())?;
+    Ok(process.status())
+  })
+  .await
+  .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn stop_api(process: State<'_, ApiProcess>) -> Result<ApiStatus, String> {
+  log::info!("API stop requested from the UI");
+  let process = process.inner().clone();
+  tauri::async_runtime::spawn_blocking(move || {
+    process.stop();
+    process.status()
+  })
+  .await
+  .map_err(|err| err.to_string())
+}
+
+/// Sends SIGTERM to the sidecar's process group, waits up to `SHUTDOWN_GRACE`
+/// for Node to exit, then SIGKILLs whatever is left of the group (Python tool
+/// runs included). The child is always reaped so no zombie is left behind.
+#[cfg(unix)]
+fn terminate(mut child: Child) {
+  let pgid = child.id() as libc::pid_t;
+  signal_group(pgid, libc::SIGTERM);
+  let deadline = Instant::now() + SHUTDOWN_GRACE;
+  let mut exited = false;
+  while Instant::now() < deadline {
+    match child.try_wait() {
+      Ok(Some(_)) => {
+        exited = true;
+        break;
+      }
+      Ok(None) => thread::sleep(Duration::from_millis(50)),
+      Err(_) => break,
+    }
+  }
+  if !exited {
+    log::warn!(
+      "API server did not exit within {}s of SIGTERM; killing it",
+      SHUTDOWN_GRACE.as_secs()
+    );
+  }
+  signal_group(pgid, libc::SIGKILL);
+  let _ = child.wait();
+}
+
+/// Windows has no signals; `taskkill /T` takes the whole tree down instead.
+#[cfg(not(unix))]
+fn terminate(mut child: Child) {
+  let _ = Command::new("taskkill")
+    .args(["/PID", &child.id().to_string(), "/T", "/F"])
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .status();
+  let _ = child.kill();
+  let _ = child.wait();
+}
+
+#[cfg(unix)]
+pub fn signal_group(pgid: libc::pid_t, signal: libc::c_int) {
+  // SAFETY: a negative pid addresses the process group we created at spawn.
+  unsafe {
+    libc::kill(-pgid, signal);
+  }
+}
+
+/// Kills anything Node left running in its group after it died on its own.
+#[cfg(unix)]
+fn kill_orphans(child: &Child) {
+  signal_group(child.id() as libc::pid_t, libc::SIGKILL);
+}
+
+#[cfg(not(unix))]
+fn kill_orphans(_child: &Child) {}
+
+/// A socket for the packaged app on Unix. Otherwise (and in source mode,
+/// which keeps a port so the API is easy to poke at) the previous port when it
+/// is still free, so an open webview keeps working, else a fresh one.
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn choose_endpoint(
+  data_dir: &Path,
+  mode: SidecarMode,
+  previous: Option<&Endpoint>,
+) -> Result<Endpoint, SidecarError> {
+  #[cfg(unix)]
+  if mode != SidecarMode::Source {
+    let path = crate::transport::socket_path(data_dir);
+    // A socket file left by a crashed sidecar would make `listen` fail.
+    let _ = fs::remove_file(&path);
+    return Ok(Endpoint::Unix(path));
+  }
+  let preferred = match mode {
+    SidecarMode::Source => Some(DEV_PORT),
+    _ => None,
+  };
+  let port = match previous
+    .and_then(Endpoint::port)
+    .or(preferred)
+    .filter(|port| port_is_free(*port))
+  {
+    Some(port) => port,
+    None => pick_free_port().map_err(SidecarError::PortUnavailable)?,
+  };
+  Ok(Endpoint::Tcp(port))
+}
+
+/// 32 random bytes, hex-encoded.
+pub(crate) fn generate_token() -> String {
+  let mut bytes = [0u8; 32];
+  getrandom::fill(&mut bytes).expect("the OS random number generator is unavailable");
+  bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Asks the OS for an unused loopback port.
+fn pick_free_port() -> io::Result<u16> {
+  let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+  Ok(listener.local_addr()?.port())
+}
+
+fn port_is_free(port: u16) -> bool {
+  TcpListener::bind((Ipv4Addr::LOCALHOST, port)).is_ok()
+}
+
+fn backoff_for(failures: u32) -> Duration {
+  let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+  INITIAL_BACKOFF.saturating_mul(factor).min(MAX_BACKOFF)
+}
+
+/// Records an exit and returns the new record plus the delay before the next
+/// spawn, or `None` for the delay when the crash-loop limit has been hit.
+fn record_exit(
+  state: &mut Supervisor,
+  exit_code: Option<i32>,
+) -> (RestartRecord, Option<Duration>) {
+  let now = Instant::now();
+  let uptime = state
+    .started_at
+    .map(|started| now.duration_since(started))
+    .unwrap_or_default();
+  if uptime >= STABLE_UPTIME {
+    state.consecutive_failures = 0;
+  }
+  state.consecutive_failures += 1;
+  state.started_at = None;
+
+  state.recent_crashes.push_back(now);
+  while let Some(first) = state.recent_crashes.front() {
+    if now.duration_since(*first) > CRASH_WINDOW {
+      state.recent_crashes.pop_front();
+    } else {
+      break;
+    }
+  }
+
+  let backoff = backoff_for(state.consecutive_failures);
+  let record = RestartRecord {
+    exit_code,
+    exited_at_ms: clock::unix_ms(),
+    backoff_ms: backoff.as_millis() as u64,
+  };
+  state.restart_count += 1;
+  state.restarts.push_back(record.clone());
+  if state.restarts.len() > MAX_RESTART_RECORDS {
+    state.restarts.pop_front();
+  }
+
+  if state.recent_crashes.len() >= MAX_CRASHES {
+    state.state = ApiState::Failed;
+    return (record, None);
+  }
+  state.state = ApiState::Restarting;
+  (record, Some(backoff))
+}
+
+/// Removes the pid record and socket of a sidecar that has exited.
+fn clear_records(data_dir: Option<&Path>, socket: Option<&Path>) {
+  if let Some(data_dir) = data_dir {
+    pidfile::clear(data_dir);
+  }
+  if let Some(socket) = socket {
+    let _ = fs::remove_file(socket);
+  }
+}
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, SidecarError> {
+  let app_data_dir = paths::app_data_dir(app).map_err(SidecarError::DataDirUnavailable)?;
+  create_dir(&app_data_dir)?;
+  Ok(app_data_dir)
+}
+
+fn create_dir(path: &Path) -> Result<(), SidecarError> {
+  fs::create_dir_all(path).map_err(|source| SidecarError::DataDirUnwritable {
+    path: path.to_path_buf(),
+    source,
+  })
+}
+
+/// Node binary, arguments and working directory for a given mode.
+struct Launch {
+  node: PathBuf,
+  args: Vec<PathBuf>,
+  cwd: PathBuf,
+  node_path: Option<PathBuf>,
+  node_env: &'static str,
+  /// The script Node runs; recorded so orphan detection can recognise it.
+  entry: PathBuf,
+}
+
+fn launch_for(app: &AppHandle, mode: SidecarMode) -> Result<Launch, SidecarError> {
+  if mode == SidecarMode::Source {
+    let api_dir = SidecarMode::source_dir();
+    let entry = api_dir.join("src").join("index.ts");
+    let tsx = api_dir
+      .join("..")
+      .join("..")
+      .join("node_modules")
+      .join("tsx")
+      .join("dist")
+      .join("cli.mjs");
+    if !entry.exists() {
+      return Err(SidecarError::MissingEntry { path: entry });
+    }
+    if !tsx.exists() {
+      return Err(SidecarError::MissingEntry { path: tsx });
+    }
+    let node = PathBuf::from("node");
+    integrity::check_node_version(&node, false)?;
+    return Ok(Launch {
+      node,
+      args: vec![tsx, entry.clone()],
+      cwd: api_dir,
+      node_path: None,
+      node_env: "development",
+      entry,
+    });
+  }
+
+  let resource_dir = app
+    .path()
+    .resource_dir()
+    .map_err(SidecarError::ResourceDirUnavailable)?;
+  let api_dir = resource_dir.join("api");
+  let entry = api_dir.join("dist").join("index.js");
+  if !entry.exists() {
+    return Err(SidecarError::MissingEntry { path: entry });
+  }
+
+  let bundled_node = resource_dir.join("bin").join("node");
+  let node = if bundled_node.exists() {
+    integrity::verify_bundle(&api_dir, Some(&bundled_node))?;
+    bundled_node
+  } else {
+    integrity::verify_bundle(&api_dir, None)?;
+    let node = PathBuf::from("node");
+    let version = integrity::check_node_version(&node, true)?;
+    integrity::warn_system_node(app, &version);
+    node
+  };
+
+  Ok(Launch {
+    node,
+    args: vec![entry.clone()],
+    cwd: api_dir,
+    node_path: Some(resource_dir.join("node_modules")),
+    node_env: "production",
+    entry,
+  })
+}
+
+/// `prisma/migrations` next to the API a given mode runs.
+pub fn migrations_dir(app: &AppHandle, mode: SidecarMode) -> Result<PathBuf, SidecarError> {
+  let api_dir = if mode == SidecarMode::Source {
+    SidecarMode::source_dir()
+  } else {
+    app
+      .path()
+      .resource_dir()
+      .map_err(SidecarError::ResourceDirUnavailable)?
+      .join("api")
+  };
+  Ok(api_dir.join("prisma").join("migrations"))
+}
+
+// The only host variables the bundled sidecar sees, plus `LC_*`. Everything
+// else is dropped so a stray variable in the user's shell can't change how the
+// API behaves; its configuration comes from the host's config.toml alone.
+const INHERITED_ENV: &[&str] = &[
+  "PATH",
+  "HOME",
+  "USER",
+  "LOGNAME",
+  "TMPDIR",
+  "TMP",
+  "TEMP",
+  "TZ",
+  "LANG",
+  "LANGUAGE",
+  "HTTP_PROXY",
+  "HTTPS_PROXY",
+  "NO_PROXY",
+  "ALL_PROXY",
+  "http_proxy",
+  "https_proxy",
+  "no_proxy",
+  "all_proxy",
+  "NODE_EXTRA_CA_CERTS",
+  "SSL_CERT_FILE",
+  "SSL_CERT_DIR",
+  // Windows needs these for Node to start at all.
+  "SYSTEMROOT",
+  "WINDIR",
+  "COMSPEC",
+  "PATHEXT",
+  "USERPROFILE",
+  "APPDATA",
+  "LOCALAPPDATA",
+  "PROGRAMDATA",
+];
+
+fn inherited_env() -> impl Iterator<Item = (OsString, OsString)> {
+  std::env::vars_os().filter(|(name, _)| {
+    let Some(name) = name.to_str() else {
+      return false;
+    };
+    name.starts_with("LC_")
+      || INHERITED_ENV.iter().any(|allowed| {
+        // Variable names are case-insensitive on Windows.
+        if cfg!(windows) {
+          allowed.eq_ignore_ascii_case(name)
+        } else {
+          *allowed == name
+        }
+      })
+  })
+}
+
+fn spawn_api(
+  app: &AppHandle,
+  mode: SidecarMode,
+  token: &str,
+  endpoint: &Endpoint,
+) -> Result<Child, SidecarError> {
+  let launch = launch_for(app, mode)?;
+  let app_data_dir = app_data_dir(app)?;
+  let config = config::load(&app_data_dir)?;
+
+  let storage_root = app_data_dir.join("storage");
+  let memory_root = app_data_dir.join("memory");
+  create_dir(&storage_root)?;
+  create_dir(&memory_root)?;
+
+  // The sidecar always starts against an up-to-date schema; it no longer
+  // creates or changes tables itself.
+  let db_path = app_data_dir.join(db::DB_FILE);
+  let applied = migrate::run(&db_path, &migrations_dir(app, mode)?)?;
+  if !applied.is_empty() {
+    log::info!("Applied {} migration(s) to {}", applied.len(), db_path.display());
+  }
+  let db_url = format!(
+    "file://{}",
+    db_path.to_string_lossy().replace(' ', "%20")
+  );
+
+  let mut command = Command::new(&launch.node);
+  command.args(&launch.args).current_dir(&launch.cwd);
+  if mode != SidecarMode::Source {
+    // Also tells the API to skip `.env`. Under `tauri dev` the developer's
+    // environment and `.env` still apply, below everything set here.
+    command
+      .env_clear()
+      .envs(inherited_env())
+      .env("PRO_CHAT_MANAGED_ENV", "1");
+  }
+  command
+    .envs(config.to_env())
+    .env("NODE_ENV", launch.node_env)
+    .env("PRO_CHAT_API_TOKEN", token)
+    .env("DATABASE_URL", db_url)
+    .env("STORAGE_PATH", storage_root)
+    .env("MEMORY_PATH", memory_root)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped());
+  if let Some(node_path) = &launch.node_path {
+    command.env("NODE_PATH", node_path);
+  }
+  match endpoint {
+    Endpoint::Tcp(port) => command.env("PORT", port.to_string()),
+    #[cfg(unix)]
+    Endpoint::Unix(path) => command.env("PRO_CHAT_API_SOCKET", path),
+  };
+  if mode == SidecarMode::Source {
+    // The dev webview is served by Vite rather than from the tauri:// origin.
+    command.env("CORS_ORIGINS", DEV_ORIGIN);
+  }
+
+  #[cfg(unix)]
+  {
+    use std::os::unix::process::CommandExt;
+    // Lead a new process group so teardown can signal Node and its children.
+    command.process_group(0);
+  }
+  resources::apply(&mut command, &config.resources);
+
+  let mut child = command.spawn().map_err(|err| match err.kind() {
+    io::ErrorKind::NotFound => SidecarError::NodeNotFound {
+      command: launch.node.clone(),
+    },
+    _ => SidecarError::Spawn(err),
+  })?;
+  log::info!("API server started (pid {}, {endpoint}, {mode:?})", child.id());
+  pidfile::write(&app_data_dir, child.id(), endpoint, &launch.entry);
+  if let Some(stdout) = child.stdout.take() {
+    logging::capture(stdout, logging::API_STDOUT, Level::Info);
+  }
+  if let Some(stderr) = child.stderr.take() {
+    logging::capture(stderr, logging::API_STDERR, Level::Warn);
+  }
+
+  Ok(child)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn backoff_doubles_up_to_the_cap() {
+    assert_eq!(backoff_for(1), INITIAL_BACKOFF);
+    assert_eq!(backoff_for(2), INITIAL_BACKOFF * 2);
+    assert_eq!(backoff_for(3), INITIAL_BACKOFF * 4);
+    assert_eq!(backoff_for(7), MAX_BACKOFF);
+    assert_eq!(backoff_for(u32::MAX), MAX_BACKOFF);
+  }
+
+  #[test]
+  fn crash_loop_limit_stops_restarts() {
+    let mut state = Supervisor::default();
+    for crash in 1..MAX_CRASHES {
+      let (record, backoff) = record_exit(&mut state, Some(1));
+      assert_eq!(backoff, Some(backoff_for(crash as u32)));
+      assert_eq!(record.exit_code, Some(1));
+      assert_eq!(state.state, ApiState::Restarting);
+    }
+    let (_, backoff) = record_exit(&mut state, None);
+    assert_eq!(backoff, None);
+    assert_eq!(state.state, ApiState::Failed);
+  }
+
+  #[test]
+  fn stable_uptime_resets_the_backoff() {
+    let mut state = Supervisor {
+      consecutive_failures: 4,
+      started_at: Instant::now().checked_sub(STABLE_UPTIME),
+      ..Supervisor::default()
+    };
+    let (record, backoff) = record_exit(&mut state, Some(0));
+    assert_eq!(backoff, Some(INITIAL_BACKOFF));
+    assert_eq!(record.backoff_ms, INITIAL_BACKOFF.as_millis() as u64);
+    assert_eq!(state.consecutive_failures, 1);
+    assert_eq!(state.started_at, None);
+  }
+
+  #[test]
+  fn short_uptime_keeps_backing_off() {
+    let mut state = Supervisor {
+      consecutive_failures: 2,
+      started_at: Some(Instant::now()),
+      ..Supervisor::default()
+    };
+    let (_, backoff) = record_exit(&mut state, Some(1));
+    assert_eq!(backoff, Some(backoff_for(3)));
+  }
+
+  #[test]
+  fn restart_history_is_bounded() {
+    let mut state = Supervisor::default();
+    for _ in 0..MAX_RESTART_RECORDS + 10 {
+      record_exit(&mut state, Some(1));
+      // Keep the crash-loop limit out of the way.
+      state.recent_crashes.clear();
+    }
+    assert_eq!(state.restarts.len(), MAX_RESTART_RECORDS);
+    assert_eq!(state.status().restart_count, MAX_RESTART_RECORDS as u64 + 10);
+  }
+}
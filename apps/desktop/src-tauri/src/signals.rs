@@ -0,0 +1,34 @@
+use tauri::AppHandle;
+
+/// Turns SIGTERM, SIGINT and SIGHUP into a regular `app.exit`, so the usual
+/// `RunEvent::Exit` teardown runs instead of the process dying on the spot.
+#[cfg(unix)]
+pub fn exit_on_termination(app: AppHandle) {
+  use std::sync::atomic::{AtomicBool, Ordering};
+  use std::thread;
+  use std::time::Duration;
+
+  static RECEIVED: AtomicBool = AtomicBool::new(false);
+
+  extern "C" fn on_signal(_signal: libc::c_int) {
+    RECEIVED.store(true, Ordering::SeqCst);
+  }
+
+  for signal in [libc::SIGTERM, libc::SIGINT, libc::SIGHUP] {
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+    unsafe {
+      libc::signal(signal, on_signal as libc::sighandler_t);
+    }
+  }
+
+  thread::spawn(move || loop {
+    thread::sleep(Duration::from_millis(200));
+    if RECEIVED.load(Ordering::SeqCst) {
+      app.exit(0);
+      return;
+    }
+  });
+}
+
+#[cfg(not(unix))]
+pub fn exit_on_termination(_app: AppHandle) {}
@@ -0,0 +1,558 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::backup::{self, ARCHIVE_SUFFIX, TIMESTAMP_FORMAT};
+use crate::clock;
+use crate::destination::Destination;
+use crate::paths;
+
+pub const EVENT: &str = "snapshots://status";
+const SETTINGS_FILE: &str = "snapshots.json";
+const PREFIX: &str = "pro-chat-snapshot";
+// Archives are built here, then handed to the destination.
+const OUTGOING_DIR: &str = "snapshot-outgoing";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+// A failed snapshot is retried sooner than the regular schedule would.
+const RETRY_AFTER: Duration = Duration::from_secs(30 * 60);
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotSettings {
+  pub enabled: bool,
+  pub interval_hours: u32,
+  pub retention: Retention,
+  pub destination: Option<Destination>,
+}
+
+impl Default for SnapshotSettings {
+  fn default() -> Self {
+    SnapshotSettings {
+      enabled: false,
+      interval_hours: 24,
+      retention: Retention::KeepLast { count: 7 },
+      destination: None,
+    }
+  }
+}
+
+impl SnapshotSettings {
+  fn validate(&self) -> Result<(), String> {
+    if !(1..=24 * 31).contains(&self.interval_hours) {
+      return Err("Snapshots can run every 1 hour up to every 31 days.".to_string());
+    }
+    match self.retention {
+      Retention::KeepLast { count: 0 } => {
+        return Err("Keep at least one snapshot.".to_string());
+      }
+      Retention::Calendar {
+        daily: 0,
+        weekly: 0,
+      } => return Err("Keep at least one daily or weekly snapshot.".to_string()),
+      _ => {}
+    }
+    match &self.destination {
+      Some(destination) => destination.validate(),
+      None if self.enabled => Err("Choose where snapshots go.".to_string()),
+      None => Ok(()),
+    }
+  }
+}
+
+/// Which snapshots survive pruning. The newest one always does.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum Retention {
+  /// The `count` most recent snapshots.
+  KeepLast { count: u32 },
+  /// The newest snapshot of each of the last `daily` days and of each of the
+  /// last `weekly` weeks that have one.
+  Calendar { daily: u32, weekly: u32 },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotRun {
+  pub finished_at_ms: u64,
+  pub ok: bool,
+  pub message: String,
+}
+
+/// `snapshots.json` in the app data directory. It can hold destination
+/// credentials, so like `remote.json` it is only readable by the owner.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotStore {
+  #[serde(default)]
+  settings: SnapshotSettings,
+  #[serde(default)]
+  last_run: Option<SnapshotRun>,
+}
+
+impl SnapshotStore {
+  fn load(data_dir: &Path) -> Self {
+    let path = data_dir.join(SETTINGS_FILE);
+    let Ok(contents) = fs::read_to_string(&path) else {
+      return SnapshotStore::default();
+    };
+    serde_json::from_str(&contents).unwrap_or_else(|err| {
+      log::warn!("Ignoring unreadable {}: {err}", path.display());
+      SnapshotStore::default()
+    })
+  }
+
+  fn save(&self, data_dir: &Path) -> io::Result<()> {
+    let json = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+    paths::write_private(&data_dir.join(SETTINGS_FILE), &json)
+  }
+
+  /// Whether the schedule calls for a snapshot at `now_ms`.
+  fn due(&self, now_ms: u64) -> bool {
+    if !self.settings.enabled || self.settings.destination.is_none() {
+      return false;
+    }
+    let interval = Duration::from_secs(u64::from(self.settings.interval_hours) * 3600);
+    match &self.last_run {
+      None => true,
+      Some(run) => {
+        let wait = if run.ok {
+          interval
+        } else {
+          interval.min(RETRY_AFTER)
+        };
+        now_ms >= run.finished_at_ms + wait.as_millis() as u64
+      }
+    }
+  }
+}
+
+/// Checks the schedule every minute and takes a snapshot when one is due. The
+/// last run is persisted, so the schedule carries over between launches.
+pub fn schedule(app: AppHandle) {
+  thread::spawn(move || loop {
+    thread::sleep(CHECK_INTERVAL);
+    let Ok(data_dir) = paths::app_data_dir(&app) else {
+      return;
+    };
+    if SnapshotStore::load(&data_dir).due(clock::unix_ms()) {
+      let _ = run(&app, &data_dir, false);
+    }
+  });
+}
+
+/// Takes a snapshot, prunes old ones and reports the outcome: always as an
+/// event, and as a notification when it failed, was asked for by hand, or
+/// worked again after a failure.
+fn run(app: &AppHandle, data_dir: &Path, manual: bool) -> Result<SnapshotRun, String> {
+  if RUNNING.swap(true, Ordering::SeqCst) {
+    return Err("A snapshot is already being taken.".to_string());
+  }
+  let store = SnapshotStore::load(data_dir);
+  let result = take_snapshot(app, data_dir, &store.settings);
+  RUNNING.store(false, Ordering::SeqCst);
+
+  let run = SnapshotRun {
+    finished_at_ms: clock::unix_ms(),
+    ok: result.is_ok(),
+    message: match result {
+      Ok(message) => message,
+      Err(err) => format!("Snapshot failed: {err}"),
+    },
+  };
+  if run.ok {
+    log::info!("{}", run.message);
+  } else {
+    log::error!("{}", run.message);
+  }
+  let recovered = store.last_run.as_ref().is_some_and(|last| !last.ok);
+  if !run.ok || manual || recovered {
+    let title = if run.ok {
+      "Snapshot saved"
+    } else {
+      "Snapshot failed"
+    };
+    let _ = app
+      .notification()
+      .builder()
+      .title(title)
+      .body(&run.message)
+      .show();
+  }
+
+  // Reloaded so settings saved while the snapshot ran are kept.
+  let mut store = SnapshotStore::load(data_dir);
+  store.last_run = Some(run.clone());
+  if let Err(err) = store.save(data_dir) {
+    log::warn!("Failed to record the snapshot result: {err}");
+  }
+  let _ = app.emit(EVENT, &run);
+  Ok(run)
+}
+
+fn take_snapshot(
+  app: &AppHandle,
+  data_dir: &Path,
+  settings: &SnapshotSettings,
+) -> io::Result<String> {
+  let destination = settings
+    .destination
+    .as_ref()
+    .ok_or_else(|| io::Error::other("no destination is configured"))?;
+  let version = app.package_info().version.to_string();
+  let archive = backup::create(data_dir, &version, &data_dir.join(OUTGOING_DIR), PREFIX)?;
+  let name = archive
+    .path
+    .file_name()
+    .map(|name| name.to_string_lossy().into_owned())
+    .unwrap_or_default();
+  let uploaded = destination.upload(&archive.path, &name);
+  let _ = fs::remove_file(&archive.path);
+  uploaded?;
+
+  let expired = expired(&destination.list()?, &settings.retention);
+  for old in &expired {
+    destination.delete(old)?;
+  }
+  Ok(match expired.len() {
+    0 => format!("Saved {name}."),
+    pruned => format!("Saved {name} and removed {pruned} older snapshot(s)."),
+  })
+}
+
+/// The newest snapshot at the configured destination and when it was taken.
+pub fn newest(data_dir: &Path) -> io::Result<Option<(String, NaiveDateTime)>> {
+  let Some(destination) = SnapshotStore::load(data_dir).settings.destination else {
+    return Ok(None);
+  };
+  Ok(
+    destination
+      .list()?
+      .into_iter()
+      .filter_map(|name| Some((snapshot_time(&name)?, name)))
+      .max()
+      .map(|(time, name)| (name, time)),
+  )
+}
+
+/// Copies snapshot `name` from the configured destination to `local`.
+pub fn download(data_dir: &Path, name: &str, local: &Path) -> io::Result<()> {
+  SnapshotStore::load(data_dir)
+    .settings
+    .destination
+    .ok_or_else(|| io::Error::other("no destination is configured"))?
+    .download(name, local)
+}
+
+fn snapshot_time(name: &str) -> Option<NaiveDateTime> {
+  let stamp = name
+    .strip_prefix(PREFIX)?
+    .strip_prefix('-')?
+    .strip_suffix(ARCHIVE_SUFFIX)?;
+  NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT).ok()
+}
+
+/// Snapshots among `names` that `retention` no longer keeps. Anything that
+/// isn't one of our snapshots is left alone.
+fn expired(names: &[String], retention: &Retention) -> Vec<String> {
+  let mut snapshots: Vec<(NaiveDateTime, &String)> = names
+    .iter()
+    .filter_map(|name| Some((snapshot_time(name)?, name)))
+    .collect();
+  snapshots.sort_by(|a, b| b.0.cmp(&a.0));
+
+  let mut keep: BTreeSet<&String> = BTreeSet::new();
+  match retention {
+    Retention::KeepLast { count } => {
+      keep.extend(snapshots.iter().take(*count as usize).map(|(_, name)| *name));
+    }
+    Retention::Calendar { daily, weekly } => {
+      let mut days = Vec::new();
+      let mut weeks = Vec::new();
+      // Newest first, so the first snapshot seen for a period is its newest.
+      for (time, name) in &snapshots {
+        let day = time.date();
+        if days.len() < *daily as usize && !days.contains(&day) {
+          days.push(day);
+          keep.insert(*name);
+        }
+        let week = time.iso_week();
+        if weeks.len() < *weekly as usize && !weeks.contains(&week) {
+          weeks.push(week);
+          keep.insert(*name);
+        }
+      }
+    }
+  }
+  if let Some((_, newest)) = snapshots.first() {
+    keep.insert(*newest);
+  }
+  snapshots
+    .iter()
+    .filter(|(_, name)| !keep.contains(name))
+    .map(|(_, name)| (*name).clone())
+    .collect()
+}
+
+fn data_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+  paths::app_data_dir(app).map_err(|err| err.to_string())
+}
+
+/// Fills in the saved secret when the webview sent none; it never receives
+/// secrets, so it can't send them back. Only a destination for the same
+/// account gets it, so pointing the settings at another server or user
+/// without re-entering the secret never sends the old one there.
+fn with_saved_secret(mut destination: Destination, saved: Option<&Destination>) -> Destination {
+  let reusable = saved
+    .filter(|saved| saved.same_account(&destination))
+    .and_then(Destination::secret)
+    .cloned();
+  match destination.secret().map(String::is_empty) {
+    None => destination.set_secret(reusable),
+    Some(true) => destination.set_secret(None),
+    Some(false) => {}
+  }
+  destination
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotView {
+  settings: SnapshotSettings,
+  has_secret: bool,
+  last_run: Option<SnapshotRun>,
+  running: bool,
+}
+
+#[tauri::command]
+pub fn snapshot_settings(app: AppHandle) -> Result<SnapshotView, String> {
+  let mut store = SnapshotStore::load(&data_dir(&app)?);
+  let has_secret = store
+    .settings
+    .destination
+    .as_ref()
+    .is_some_and(|destination| destination.secret().is_some());
+  if let Some(destination) = store.settings.destination.as_mut() {
+    destination.set_secret(None);
+  }
+  Ok(SnapshotView {
+    settings: store.settings,
+    has_secret,
+    last_run: store.last_run,
+    running: RUNNING.load(Ordering::SeqCst),
+  })
+}
+
+/// Saves the schedule. A destination secret left out keeps the saved one; an
+/// empty one removes it.
+#[tauri::command]
+pub fn save_snapshot_settings(app: AppHandle, settings: SnapshotSettings) -> Result<(), String> {
+  let dir = data_dir(&app)?;
+  let mut store = SnapshotStore::load(&dir);
+  let mut settings = settings;
+  settings.destination = settings
+    .destination
+    .map(|destination| with_saved_secret(destination, store.settings.destination.as_ref()));
+  settings.validate()?;
+  store.settings = settings;
+  store.save(&dir).map_err(|err| err.to_string())
+}
+
+/// "Snapshot now" in settings; runs even when the schedule is off.
+#[tauri::command]
+pub async fn run_snapshot(app: AppHandle) -> Result<SnapshotRun, String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    let dir = data_dir(&app)?;
+    run(&app, &dir, true)
+  })
+  .await
+  .map_err(|err| err.to_string())?
+}
+
+/// Writes, lists and deletes a small file at `destination` to prove the
+/// settings work before they are saved.
+#[tauri::command]
+pub async fn test_snapshot_destination(
+  app: AppHandle,
+  destination: Destination,
+) -> Result<(), String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    let dir = data_dir(&app)?;
+    let saved = SnapshotStore::load(&dir).settings.destination;
+    let destination = with_saved_secret(destination, saved.as_ref());
+    destination.validate()?;
+
+    let outgoing = dir.join(OUTGOING_DIR);
+    let name = "pro-chat-destination-test.txt";
+    let probe = outgoing.join(name);
+    let result = fs::create_dir_all(&outgoing)
+      .and_then(|()| fs::write(&probe, b"pro-chat snapshot destination test\n"))
+      .and_then(|()| destination.upload(&probe, name))
+      .and_then(|()| destination.list())
+      .and_then(|names| {
+        if names.iter().any(|found| found == name) {
+          destination.delete(name)
+        } else {
+          Err(io::Error::other("the test file was stored but is not listed"))
+        }
+      });
+    let _ = fs::remove_file(&probe);
+    result.map_err(|err| err.to_string())
+  })
+  .await
+  .map_err(|err| err.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn s3(endpoint: &str, bucket: &str, access_key: &str, secret: Option<&str>) -> Destination {
+    Destination::S3 {
+      endpoint: endpoint.to_string(),
+      region: "us-east-1".to_string(),
+      bucket: bucket.to_string(),
+      prefix: String::new(),
+      access_key: access_key.to_string(),
+      secret_key: secret.map(str::to_string),
+    }
+  }
+
+  fn webdav(url: &str, username: Option<&str>, password: Option<&str>) -> Destination {
+    Destination::WebDav {
+      url: url.to_string(),
+      username: username.map(str::to_string),
+      password: password.map(str::to_string),
+    }
+  }
+
+  fn secret_after(sent: Destination, saved: &Destination) -> Option<String> {
+    with_saved_secret(sent, Some(saved)).secret().cloned()
+  }
+
+  fn snapshot(stamp: &str) -> String {
+    format!("{PREFIX}-{stamp}{ARCHIVE_SUFFIX}")
+  }
+
+  fn names(stamps: &[&str]) -> Vec<String> {
+    stamps.iter().map(|stamp| snapshot(stamp)).collect()
+  }
+
+  #[test]
+  fn saved_secret_is_reused_for_the_same_account() {
+    let saved = s3("https://s3.example", "backups", "AKIA", Some("old"));
+    let sent = s3("https://s3.example/", "backups", "AKIA", None);
+    assert_eq!(secret_after(sent, &saved).as_deref(), Some("old"));
+
+    let saved = webdav("https://dav.example/files", Some("me"), Some("pw"));
+    let sent = webdav("https://dav.example/files", Some("me"), None);
+    assert_eq!(secret_after(sent, &saved).as_deref(), Some("pw"));
+  }
+
+  #[test]
+  fn saved_secret_stays_with_its_account() {
+    let saved = s3("https://s3.example", "backups", "AKIA", Some("old"));
+    for sent in [
+      s3("https://evil.example", "backups", "AKIA", None),
+      s3("https://s3.example", "other", "AKIA", None),
+      s3("https://s3.example", "backups", "AKIB", None),
+      webdav("https://s3.example", None, None),
+    ] {
+      assert_eq!(secret_after(sent, &saved), None);
+    }
+
+    let saved = webdav("https://dav.example/files", Some("me"), Some("pw"));
+    for sent in [
+      webdav("https://evil.example/files", Some("me"), None),
+      webdav("https://dav.example/files", Some("you"), None),
+      webdav("https://dav.example/files", None, None),
+    ] {
+      assert_eq!(secret_after(sent, &saved), None);
+    }
+  }
+
+  #[test]
+  fn sent_secret_wins_and_empty_clears() {
+    let saved = s3("https://s3.example", "backups", "AKIA", Some("old"));
+    let sent = s3("https://s3.example", "backups", "AKIA", Some("new"));
+    assert_eq!(secret_after(sent, &saved).as_deref(), Some("new"));
+    let sent = s3("https://s3.example", "backups", "AKIA", Some(""));
+    assert_eq!(secret_after(sent, &saved), None);
+  }
+
+  #[test]
+  fn keep_last_expires_the_oldest_and_ignores_other_files() {
+    let mut listed = names(&["20260103-000000", "20260101-000000", "20260104-000000"]);
+    listed.push(snapshot("20260102-000000"));
+    listed.push("notes.txt".to_string());
+    listed.push(format!("{PREFIX}-yesterday{ARCHIVE_SUFFIX}"));
+    assert_eq!(
+      expired(&listed, &Retention::KeepLast { count: 2 }),
+      names(&["20260102-000000", "20260101-000000"])
+    );
+  }
+
+  #[test]
+  fn the_newest_snapshot_is_always_kept() {
+    let listed = names(&["20260101-000000", "20260102-000000"]);
+    assert_eq!(
+      expired(&listed, &Retention::KeepLast { count: 0 }),
+      names(&["20260101-000000"])
+    );
+    let calendar = Retention::Calendar {
+      daily: 0,
+      weekly: 0,
+    };
+    assert_eq!(expired(&listed, &calendar), names(&["20260101-000000"]));
+  }
+
+  #[test]
+  fn calendar_keeps_the_newest_snapshot_of_each_day() {
+    let listed = names(&[
+      "20260105-100000",
+      "20260104-220000",
+      "20260104-080000",
+      "20260103-120000",
+    ]);
+    let retention = Retention::Calendar {
+      daily: 2,
+      weekly: 0,
+    };
+    assert_eq!(
+      expired(&listed, &retention),
+      names(&["20260104-080000", "20260103-120000"])
+    );
+  }
+
+  #[test]
+  fn calendar_weeks_follow_iso_weeks_across_the_year_boundary() {
+    // Mon 2026-01-05 starts week 2. Week 1 of 2026 runs from Mon 2025-12-29
+    // to Sun 2026-01-04; Sun 2025-12-28 is still in week 52 of 2025.
+    let listed = names(&[
+      "20260105-100000",
+      "20260104-220000",
+      "20260104-080000",
+      "20251229-090000",
+      "20251228-090000",
+      "20251227-090000",
+    ]);
+    let retention = Retention::Calendar {
+      daily: 1,
+      weekly: 3,
+    };
+    assert_eq!(
+      expired(&listed, &retention),
+      names(&["20260104-080000", "20251229-090000", "20251227-090000"])
+    );
+  }
+}
@@ -0,0 +1,265 @@
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State, WebviewWindow, WebviewWindowBuilder};
+
+use crate::error::SidecarError;
+use crate::health;
+use crate::logging::LogHistory;
+use crate::paths;
+use crate::remote::{self, RemoteBackend};
+use crate::repair;
+use crate::sidecar::{ApiProcess, ApiState, SidecarMode};
+use crate::transport::Endpoint;
+use crate::upgrade::{self, Upgrade};
+
+pub const STATUS_EVENT: &str = "startup://status";
+pub const MAIN_WINDOW: &str = "main";
+pub const SPLASH_WINDOW: &str = "splash";
+
+// The API seeds models and opens its stores on boot, which can be slow on a
+// cold disk, so be generous.
+const READY_TIMEOUT: Duration = Duration::from_secs(90);
+const SLOW_AFTER: Duration = Duration::from_secs(8);
+const PROBE_INTERVAL: Duration = Duration::from_millis(250);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+// Log lines appended to the "copy details" report.
+const DETAIL_LOG_LINES: usize = 30;
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "state", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum StartupStatus {
+  Starting { elapsed_ms: u64, slow: bool },
+  Ready,
+  Failed {
+    kind: String,
+    message: String,
+    hint: String,
+    details: String,
+  },
+}
+
+pub struct StartupState(Mutex<StartupStatus>);
+
+impl Default for StartupState {
+  fn default() -> Self {
+    StartupState(Mutex::new(StartupStatus::Starting {
+      elapsed_ms: 0,
+      slow: false,
+    }))
+  }
+}
+
+impl StartupState {
+  pub fn is_ready(&self) -> bool {
+    matches!(self.0.lock().as_deref(), Ok(StartupStatus::Ready))
+  }
+}
+
+#[tauri::command]
+pub fn startup_status(state: State<'_, StartupState>) -> Option<StartupStatus> {
+  state.0.lock().ok().map(|status| status.clone())
+}
+
+fn set_status(app: &AppHandle, status: StartupStatus) {
+  if let Some(state) = app.try_state::<StartupState>() {
+    if let Ok(mut guard) = state.0.lock() {
+      *guard = status.clone();
+    }
+  }
+  let _ = app.emit(STATUS_EVENT, status);
+}
+
+/// Starts (or restarts) the sidecar and waits for it in the background. A
+/// remote backend is only checked for reachability; when the developer runs
+/// the API by hand, the UI opens straight away.
+pub fn begin(app: &AppHandle) {
+  set_status(
+    app,
+    StartupStatus::Starting {
+      elapsed_ms: 0,
+      slow: false,
+    },
+  );
+  let remote = app
+    .try_state::<RemoteBackend>()
+    .and_then(|backend| backend.target().cloned());
+  if let Some(remote) = remote {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+      match remote::check(&remote.url, remote.token.as_deref()).await {
+        Ok(()) => mark_ready(&app),
+        Err(reason) => mark_failed(
+          &app,
+          &SidecarError::RemoteUnreachable {
+            url: remote.url,
+            reason,
+          },
+        ),
+      }
+    });
+    return;
+  }
+  let Some(process) = app.try_state::<ApiProcess>() else {
+    return;
+  };
+  if process.mode() == SidecarMode::External {
+    mark_ready(app);
+    return;
+  }
+  let process = process.inner().clone();
+  let app = app.clone();
+  // Checking and backing up the database can take a moment; keep it off the
+  // main thread so the splash screen stays responsive.
+  thread::spawn(move || {
+    if let Err(err) = repair::check(&app) {
+      mark_failed(&app, &err);
+      return;
+    }
+    let upgrade = match upgrade::prepare(&app, &process) {
+      Ok(upgrade) => upgrade,
+      Err(err) => {
+        mark_failed(&app, &err);
+        return;
+      }
+    };
+    match process.start() {
+      Ok(endpoint) => wait_for_health(app, endpoint, upgrade),
+      Err(err) => {
+        let err = match upgrade {
+          Some(upgrade) => upgrade.fail(&app, err),
+          None => err,
+        };
+        mark_failed(&app, &err);
+      }
+    }
+  });
+}
+
+/// Invoked by the error screen's "Retry" button.
+#[tauri::command]
+pub async fn retry_startup(app: AppHandle) {
+  log::info!("Retrying API startup");
+  begin(&app);
+}
+
+/// Polls the sidecar's health endpoint in the background and reveals the main
+/// window once it answers. It reports a failure after `READY_TIMEOUT`, or as
+/// soon as the supervisor gives up on a crash loop. A pending upgrade is
+/// committed by the first answer and rolled back otherwise.
+pub fn wait_for_health(app: AppHandle, endpoint: Endpoint, upgrade: Option<Upgrade>) {
+  thread::spawn(move || {
+    let started = Instant::now();
+    loop {
+      if health::probe(&endpoint, PROBE_TIMEOUT) {
+        if let Some(upgrade) = upgrade {
+          upgrade.commit();
+        }
+        mark_ready(&app);
+        return;
+      }
+      let elapsed = started.elapsed();
+      let status = app.try_state::<ApiProcess>().map(|process| process.status());
+      let failure = match status {
+        Some(status) if status.state == ApiState::Failed => Some(SidecarError::CrashLoop {
+          exit_code: status.last_exit_code,
+        }),
+        _ if elapsed >= READY_TIMEOUT => Some(SidecarError::HealthTimeout {
+          seconds: READY_TIMEOUT.as_secs(),
+        }),
+        _ => None,
+      };
+      if let Some(err) = failure {
+        let err = match upgrade {
+          Some(upgrade) => upgrade.fail(&app, err),
+          None => err,
+        };
+        mark_failed(&app, &err);
+        return;
+      }
+      set_status(
+        &app,
+        StartupStatus::Starting {
+          elapsed_ms: elapsed.as_millis() as u64,
+          slow: elapsed >= SLOW_AFTER,
+        },
+      );
+      thread::sleep(PROBE_INTERVAL);
+    }
+  });
+}
+
+/// Opens the main window and dismisses the splash screen. The main window is
+/// only created here (`"create": false` in tauri.conf.json) so the UI's first
+/// requests can't race the API's bootstrap.
+pub fn mark_ready(app: &AppHandle) {
+  set_status(app, StartupStatus::Ready);
+  let window = match app.get_webview_window(MAIN_WINDOW) {
+    Some(window) => Some(window),
+    None => create_main_window(app),
+  };
+  if let Some(window) = window {
+    let _ = window.show();
+    let _ = window.set_focus();
+  }
+  if let Some(splash) = app.get_webview_window(SPLASH_WINDOW) {
+    let _ = splash.close();
+  }
+}
+
+fn create_main_window(app: &AppHandle) -> Option<WebviewWindow> {
+  let config = app
+    .config()
+    .app
+    .windows
+    .iter()
+    .find(|window| window.label == MAIN_WINDOW)?;
+  match WebviewWindowBuilder::from_config(app, config).and_then(|builder| builder.build()) {
+    Ok(window) => Some(window),
+    Err(err) => {
+      log::error!("Failed to create the main window: {err}");
+      None
+    }
+  }
+}
+
+/// Leaves the splash window up as an error screen instead of opening a UI that
+/// can't reach anything.
+pub fn mark_failed(app: &AppHandle, err: &SidecarError) {
+  log::error!("API server failed to start: {err}");
+  set_status(
+    app,
+    StartupStatus::Failed {
+      kind: err.kind().to_string(),
+      message: err.to_string(),
+      hint: err.hint(),
+      details: failure_details(app, err),
+    },
+  );
+}
+
+/// Plain-text report for the "Copy details" button.
+fn failure_details(app: &AppHandle, err: &SidecarError) -> String {
+  let mut details = format!(
+    "pro-chat {} ({} {})\nError: {err}\nKind: {}\nDebug: {err:?}\n",
+    app.package_info().version,
+    std::env::consts::OS,
+    std::env::consts::ARCH,
+    err.kind(),
+  );
+  if let Ok(data_dir) = paths::app_data_dir(app) {
+    details.push_str(&format!("Data directory: {}\n", data_dir.display()));
+  }
+  if let Some(history) = app.try_state::<LogHistory>() {
+    details.push_str("\nRecent log:\n");
+    for entry in history.recent(DETAIL_LOG_LINES) {
+      details.push_str(&format!(
+        "{} {} [{}] {}\n",
+        entry.timestamp, entry.level, entry.source, entry.message
+      ));
+    }
+  }
+  details
+}
@@ -0,0 +1,141 @@
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+/// Where the sidecar listens. Unix hosts use a socket in the data directory,
+/// which only this user can reach and the webview talks to through the
+/// `prochat-api://` protocol; Windows falls back to a loopback port.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Endpoint {
+  Tcp(u16),
+  #[cfg(unix)]
+  Unix(PathBuf),
+}
+
+impl Endpoint {
+  pub fn port(&self) -> Option<u16> {
+    match self {
+      Endpoint::Tcp(port) => Some(*port),
+      #[cfg(unix)]
+      Endpoint::Unix(_) => None,
+    }
+  }
+
+  pub fn socket(&self) -> Option<&Path> {
+    match self {
+      Endpoint::Tcp(_) => None,
+      #[cfg(unix)]
+      Endpoint::Unix(path) => Some(path),
+    }
+  }
+}
+
+impl std::fmt::Display for Endpoint {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Endpoint::Tcp(port) => write!(f, "port {port}"),
+      #[cfg(unix)]
+      Endpoint::Unix(path) => write!(f, "socket {}", path.display()),
+    }
+  }
+}
+
+/// An open connection to the sidecar, over whichever transport it listens on.
+pub enum Connection {
+  Tcp(TcpStream),
+  #[cfg(unix)]
+  Unix(UnixStream),
+}
+
+impl Connection {
+  pub fn open(endpoint: &Endpoint, timeout: Duration) -> io::Result<Self> {
+    let connection = match endpoint {
+      Endpoint::Tcp(port) => {
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, *port));
+        Connection::Tcp(TcpStream::connect_timeout(&addr, timeout)?)
+      }
+      // Connecting to a local socket doesn't block long enough to need a timeout.
+      #[cfg(unix)]
+      Endpoint::Unix(path) => Connection::Unix(UnixStream::connect(path)?),
+    };
+    connection.set_write_timeout(Some(timeout))?;
+    Ok(connection)
+  }
+
+  pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+    match self {
+      Connection::Tcp(stream) => stream.set_read_timeout(timeout),
+      #[cfg(unix)]
+      Connection::Unix(stream) => stream.set_read_timeout(timeout),
+    }
+  }
+
+  fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+    match self {
+      Connection::Tcp(stream) => stream.set_write_timeout(timeout),
+      #[cfg(unix)]
+      Connection::Unix(stream) => stream.set_write_timeout(timeout),
+    }
+  }
+
+  pub fn try_clone(&self) -> io::Result<Self> {
+    Ok(match self {
+      Connection::Tcp(stream) => Connection::Tcp(stream.try_clone()?),
+      #[cfg(unix)]
+      Connection::Unix(stream) => Connection::Unix(stream.try_clone()?),
+    })
+  }
+
+  /// Closes both directions, which also unblocks a thread reading from a clone.
+  pub fn shutdown(&self) {
+    let _ = match self {
+      Connection::Tcp(stream) => stream.shutdown(Shutdown::Both),
+      #[cfg(unix)]
+      Connection::Unix(stream) => stream.shutdown(Shutdown::Both),
+    };
+  }
+}
+
+impl Read for Connection {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    match self {
+      Connection::Tcp(stream) => stream.read(buf),
+      #[cfg(unix)]
+      Connection::Unix(stream) => stream.read(buf),
+    }
+  }
+}
+
+impl Write for Connection {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    match self {
+      Connection::Tcp(stream) => stream.write(buf),
+      #[cfg(unix)]
+      Connection::Unix(stream) => stream.write(buf),
+    }
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    match self {
+      Connection::Tcp(stream) => stream.flush(),
+      #[cfg(unix)]
+      Connection::Unix(stream) => stream.flush(),
+    }
+  }
+}
+
+/// `<data_dir>/api.sock`, or a per-process path in the temp directory when
+/// that would exceed the ~104-byte limit on socket paths.
+#[cfg(unix)]
+pub fn socket_path(data_dir: &Path) -> PathBuf {
+  let path = data_dir.join("api.sock");
+  if path.as_os_str().len() < 100 {
+    path
+  } else {
+    std::env::temp_dir().join(format!("pro-chat-{}.sock", std::process::id()))
+  }
+}
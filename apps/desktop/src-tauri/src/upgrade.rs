@@ -0,0 +1,264 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::backup::{self, BACKUP_DIR, TIMESTAMP_FORMAT};
+use crate::db;
+use crate::error::SidecarError;
+use crate::paths;
+use crate::sidecar::ApiProcess;
+
+const MARKER_FILE: &str = "version.json";
+const BACKUP_PREFIX: &str = "pro-chat-pre-upgrade";
+// Pre-upgrade copies kept. A failing upgrade takes a new copy on every launch,
+// so without a limit they pile up.
+const KEEP_BACKUPS: usize = 3;
+
+/// `version.json` in the app data directory: the last version whose sidecar
+/// came up healthy.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Marker {
+  version: String,
+}
+
+impl Marker {
+  fn load(data_dir: &Path) -> Option<Self> {
+    let path = data_dir.join(MARKER_FILE);
+    let contents = fs::read_to_string(&path).ok()?;
+    serde_json::from_str(&contents)
+      .map_err(|err| log::warn!("Ignoring unreadable {}: {err}", path.display()))
+      .ok()
+  }
+
+  fn save(&self, data_dir: &Path) -> io::Result<()> {
+    let json = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+    paths::write_private(&data_dir.join(MARKER_FILE), &json)
+  }
+}
+
+/// A version change in progress. It is committed once the new sidecar answers
+/// its first health check and rolled back if it never does.
+#[derive(Debug)]
+pub struct Upgrade {
+  data_dir: PathBuf,
+  from: Option<String>,
+  to: String,
+  /// Copy of the database taken before anything touched it; `None` on a fresh
+  /// install, where there is nothing to protect.
+  backup: Option<PathBuf>,
+}
+
+/// Runs before the sidecar is spawned. When the app version differs from the
+/// one that last started, the database is copied into `backups/` so that a
+/// migration `spawn_api` applies, or a new sidecar that won't come up, can be
+/// undone through [`Upgrade::fail`].
+pub fn prepare(app: &AppHandle, process: &ApiProcess) -> Result<Option<Upgrade>, SidecarError> {
+  let data_dir = paths::app_data_dir(app).map_err(SidecarError::DataDirUnavailable)?;
+  let to = app.package_info().version.to_string();
+  let marker = Marker::load(&data_dir);
+  if marker.as_ref().is_some_and(|marker| marker.version == to) {
+    return Ok(None);
+  }
+
+  let mut upgrade = Upgrade {
+    from: marker.map(|marker| marker.version),
+    to,
+    backup: None,
+    data_dir,
+  };
+  let db_path = upgrade.data_dir.join(db::DB_FILE);
+  if !db_path.exists() {
+    // A fresh install: the migrations create the schema from scratch.
+    return Ok(Some(upgrade));
+  }
+
+  // The previous sidecar may still hold the database open after a failed start.
+  process.stop();
+  log::info!(
+    "Upgrading from pro-chat {} to {}; backing up the database first",
+    upgrade.from_label(),
+    upgrade.to
+  );
+  let backup_dir = upgrade.data_dir.join(BACKUP_DIR);
+  let backup_path = take_backup(&db_path, &backup_dir, upgrade.from.as_deref())
+    .map_err(|source| SidecarError::DataDirUnwritable {
+      path: backup_dir.clone(),
+      source,
+    })?;
+  upgrade.backup = Some(backup_path);
+  Ok(Some(upgrade))
+}
+
+/// Copies the database into `backup_dir` and prunes older copies down to
+/// `KEEP_BACKUPS`, this one included.
+fn take_backup(db_path: &Path, backup_dir: &Path, from: Option<&str>) -> io::Result<PathBuf> {
+  let backup_path = backup_dir.join(format!(
+    "{BACKUP_PREFIX}-{}-{}.db",
+    from.unwrap_or("unknown"),
+    chrono::Local::now().format(TIMESTAMP_FORMAT)
+  ));
+  fs::create_dir_all(backup_dir)?;
+  backup::snapshot_database(db_path, &backup_path)?;
+  prune_backups(backup_dir, KEEP_BACKUPS);
+  Ok(backup_path)
+}
+
+impl Upgrade {
+  fn from_label(&self) -> &str {
+    self.from.as_deref().unwrap_or("an earlier version")
+  }
+
+  /// Records the new version once its sidecar is healthy.
+  pub fn commit(self) {
+    let marker = Marker { version: self.to };
+    if let Err(err) = marker.save(&self.data_dir) {
+      log::warn!("Failed to record the app version: {err}");
+    }
+  }
+
+  /// Turns a startup failure into an upgrade failure, rolling the database
+  /// back first. Without a backup there is nothing to undo and `cause` stands.
+  pub fn fail(self, app: &AppHandle, cause: SidecarError) -> SidecarError {
+    let Some(backup) = self.backup.clone() else {
+      return cause;
+    };
+    let reason = cause.to_string();
+    let database = self.data_dir.join(db::DB_FILE);
+    if let Some(process) = app.try_state::<ApiProcess>() {
+      process.stop();
+    }
+    let restored = match restore_database(&backup, &database) {
+      Ok(()) => {
+        log::warn!("Upgrade failed ({reason}); restored the database from {}", backup.display());
+        true
+      }
+      Err(err) => {
+        log::error!("Upgrade failed ({reason}) and restoring {} failed: {err}", backup.display());
+        false
+      }
+    };
+    SidecarError::UpgradeFailed {
+      from: self.from_label().to_string(),
+      to: self.to,
+      reason,
+      database,
+      backup,
+      restored,
+    }
+  }
+}
+
+/// Deletes all but the newest `keep` pre-upgrade copies in `dir`. Anything
+/// else in there is left alone.
+fn prune_backups(dir: &Path, keep: usize) {
+  let Ok(entries) = fs::read_dir(dir) else {
+    return;
+  };
+  let mut backups: Vec<(chrono::NaiveDateTime, PathBuf)> = entries
+    .flatten()
+    .map(|entry| entry.path())
+    .filter_map(|path| {
+      let name = path.file_name()?.to_str()?;
+      let stem = name.strip_prefix(BACKUP_PREFIX)?.strip_suffix(".db")?;
+      Some((backup::stamp_time(stem)?, path))
+    })
+    .collect();
+  backups.sort_by(|a, b| b.0.cmp(&a.0));
+  for (_, path) in backups.into_iter().skip(keep) {
+    match fs::remove_file(&path) {
+      Ok(()) => log::info!("Removed old pre-upgrade backup {}", path.display()),
+      Err(err) => log::warn!("Failed to remove {}: {err}", path.display()),
+    }
+  }
+}
+
+/// Copies `backup` over the database via a temporary file, dropping journal
+/// files so SQLite doesn't replay them into the restored copy.
+fn restore_database(backup: &Path, database: &Path) -> io::Result<()> {
+  let tmp = database.with_extension("db.restore");
+  fs::copy(backup, &tmp)?;
+  for suffix in db::JOURNAL_SUFFIXES {
+    let mut journal = database.as_os_str().to_owned();
+    journal.push(suffix);
+    match fs::remove_file(PathBuf::from(journal)) {
+      Ok(()) => {}
+      Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+      Err(err) => return Err(err),
+    }
+  }
+  fs::rename(&tmp, database)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::TempDir;
+
+  #[test]
+  fn prune_keeps_the_newest_pre_upgrade_copies() {
+    let tmp = TempDir::new();
+    let names = [
+      "pro-chat-pre-upgrade-1.0.0-20260101-000000.db",
+      "pro-chat-pre-upgrade-1.1.0-20260201-000000.db",
+      "pro-chat-pre-upgrade-1.2.0-beta.1-20260301-000000.db",
+      "pro-chat-pre-upgrade-1.2.0-beta.1-20260302-000000.db",
+      "pro-chat-pre-upgrade-unknown-20260401-000000.db",
+      "pro-chat-20250101-000000.tar.gz",
+      "pro-chat-pre-upgrade-notes.db",
+    ];
+    for name in names {
+      fs::write(tmp.path().join(name), b"").unwrap();
+    }
+
+    prune_backups(tmp.path(), 3);
+
+    let mut left: Vec<String> = fs::read_dir(tmp.path())
+      .unwrap()
+      .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+      .collect();
+    left.sort();
+    assert_eq!(
+      left,
+      [
+        "pro-chat-20250101-000000.tar.gz",
+        "pro-chat-pre-upgrade-1.2.0-beta.1-20260301-000000.db",
+        "pro-chat-pre-upgrade-1.2.0-beta.1-20260302-000000.db",
+        "pro-chat-pre-upgrade-notes.db",
+        "pro-chat-pre-upgrade-unknown-20260401-000000.db",
+      ]
+    );
+  }
+
+  #[test]
+  fn a_failing_upgrade_keeps_a_bounded_number_of_copies() {
+    let tmp = TempDir::new();
+    let db_path = tmp.path().join(db::DB_FILE);
+    rusqlite::Connection::open(&db_path)
+      .unwrap()
+      .execute_batch("CREATE TABLE note (body TEXT);")
+      .unwrap();
+    let backup_dir = tmp.path().join(BACKUP_DIR);
+    fs::create_dir_all(&backup_dir).unwrap();
+    // Copies left by earlier launches whose upgrade failed.
+    for day in 1..=5 {
+      let name = format!("{BACKUP_PREFIX}-1.0.0-2026010{day}-000000.db");
+      fs::write(backup_dir.join(name), b"").unwrap();
+    }
+
+    let taken = take_backup(&db_path, &backup_dir, Some("1.0.0")).unwrap();
+
+    let mut left: Vec<PathBuf> = fs::read_dir(&backup_dir)
+      .unwrap()
+      .map(|entry| entry.unwrap().path())
+      .collect();
+    left.sort();
+    assert_eq!(left.len(), KEEP_BACKUPS);
+    assert!(left.contains(&taken));
+    assert!(left.contains(&backup_dir.join(format!("{BACKUP_PREFIX}-1.0.0-20260105-000000.db"))));
+    assert!(!left.contains(&backup_dir.join(format!("{BACKUP_PREFIX}-1.0.0-20260103-000000.db"))));
+  }
+}
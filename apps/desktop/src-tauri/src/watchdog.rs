@@ -0,0 +1,169 @@
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::clock;
+use crate::config;
+use crate::db;
+use crate::health;
+use crate::paths;
+use crate::sidecar::{ApiProcess, ApiState};
+use crate::startup::StartupState;
+
+pub const EVENT: &str = "api://watchdog";
+
+// A stream that wrote progress this recently is still doing useful work.
+const LIVE_STREAM_WINDOW: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug)]
+pub struct WatchdogConfig {
+  pub interval: Duration,
+  pub probe_timeout: Duration,
+  pub failure_threshold: u32,
+  /// Longest a restart may wait on in-flight streams before it goes ahead.
+  pub max_deferral: Duration,
+}
+
+impl Default for WatchdogConfig {
+  fn default() -> Self {
+    WatchdogConfig {
+      interval: Duration::from_secs(15),
+      probe_timeout: Duration::from_secs(5),
+      failure_threshold: 3,
+      max_deferral: Duration::from_secs(120),
+    }
+  }
+}
+
+impl WatchdogConfig {
+  /// Defaults with whatever the `[watchdog]` section of `config.toml` sets.
+  pub fn load(data_dir: &Path) -> Self {
+    let settings = config::load(data_dir)
+      .map(|config| config.watchdog)
+      .unwrap_or_default();
+    let defaults = WatchdogConfig::default();
+    WatchdogConfig {
+      interval: settings
+        .interval_secs
+        .map(Duration::from_secs)
+        .unwrap_or(defaults.interval),
+      failure_threshold: settings.failure_threshold.unwrap_or(defaults.failure_threshold),
+      max_deferral: settings
+        .max_deferral_secs
+        .map(Duration::from_secs)
+        .unwrap_or(defaults.max_deferral),
+      ..defaults
+    }
+  }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "action", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum WatchdogEvent {
+  Unhealthy { consecutive_failures: u32 },
+  Deferred { live_streams: u32 },
+  Restarting { consecutive_failures: u32 },
+  Recovered,
+}
+
+/// Probes `/api/health` every `interval` and restarts a sidecar that is alive
+/// but no longer answering. The exit watcher in `sidecar` only catches
+/// processes that actually die. Settings are reread each round, so edits to
+/// `config.toml` apply without a restart.
+pub fn spawn(app: AppHandle) {
+  thread::spawn(move || {
+    let Ok(data_dir) = paths::app_data_dir(&app) else {
+      return;
+    };
+    let mut failures = 0u32;
+    let mut deferred_since: Option<Instant> = None;
+    loop {
+      let config = WatchdogConfig::load(&data_dir);
+      thread::sleep(config.interval);
+
+      let ready = app
+        .try_state::<StartupState>()
+        .is_some_and(|state| state.is_ready());
+      let Some(process) = app.try_state::<ApiProcess>() else {
+        continue;
+      };
+      let status = process.status();
+      // Startup and crash restarts have their own timeouts; only judge a
+      // sidecar that is supposed to be up.
+      let endpoint = process
+        .endpoint()
+        .filter(|_| ready && status.state == ApiState::Running);
+      let Some(endpoint) = endpoint else {
+        failures = 0;
+        deferred_since = None;
+        continue;
+      };
+
+      if health::probe(&endpoint, config.probe_timeout) {
+        if failures > 0 {
+          log::info!("API server is answering health checks again");
+          emit(&app, WatchdogEvent::Recovered);
+        }
+        failures = 0;
+        deferred_since = None;
+        continue;
+      }
+
+      failures += 1;
+      log::warn!(
+        "API health check failed ({failures}/{})",
+        config.failure_threshold
+      );
+      emit(
+        &app,
+        WatchdogEvent::Unhealthy {
+          consecutive_failures: failures,
+        },
+      );
+      if failures < config.failure_threshold {
+        continue;
+      }
+
+      let waited = deferred_since.get_or_insert_with(Instant::now).elapsed();
+      let live_streams = live_streams(&app);
+      if live_streams > 0 && waited < config.max_deferral {
+        log::warn!("Deferring API restart; {live_streams} stream(s) still in progress");
+        emit(&app, WatchdogEvent::Deferred { live_streams });
+        continue;
+      }
+
+      log::error!("API server unresponsive after {failures} health checks; restarting it");
+      emit(
+        &app,
+        WatchdogEvent::Restarting {
+          consecutive_failures: failures,
+        },
+      );
+      if let Err(err) = process.start() {
+        log::error!("Watchdog failed to restart the API server: {err}");
+      }
+      failures = 0;
+      deferred_since = None;
+    }
+  });
+}
+
+/// Streams that made progress recently. Best effort: if the database can't be
+/// read the restart simply isn't deferred.
+pub fn live_streams(app: &AppHandle) -> u32 {
+  let Ok(data_dir) = paths::app_data_dir(app) else {
+    return 0;
+  };
+  let since_ms = clock::unix_ms().saturating_sub(LIVE_STREAM_WINDOW.as_millis() as u64) as i64;
+  db::live_stream_count(&data_dir.join(db::DB_FILE), since_ms).unwrap_or_else(|err| {
+    log::debug!("Could not check for active streams: {err}");
+    0
+  })
+}
+
+fn emit(app: &AppHandle, event: WatchdogEvent) {
+  let _ = app.emit(EVENT, event);
+}
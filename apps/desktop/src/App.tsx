@@ -19,12 +19,51 @@ import {
   updateSettings,
   uploadFiles,
 } from './api';
-import type { ActiveStreamInfo, Attachment, ModelInfo, Settings, ThreadSummary, UIMessage, UsageStats } from './types';
+import {
+  checkRemote,
+  createBackup,
+  deleteRemoteProfile,
+  fetchSnapshotSettings,
+  fetchApiStatus,
+  fetchHostConfig,
+  fetchRecentLogs,
+  fetchRemoteSettings,
+  listBackups,
+  restartApi,
+  restoreBackup,
+  runSnapshot,
+  saveRemoteProfile,
+  saveSnapshotSettings,
+  setActiveRemoteProfile,
+  subscribeToApiStatus,
+  subscribeToLogs,
+  subscribeToSnapshots,
+  testSnapshotDestination,
+  updateHostConfig,
+} from './desktop';
+import type {
+  ActiveStreamInfo,
+  ApiStatus,
+  Attachment,
+  BackupInfo,
+  HostConfig,
+  LogEntry,
+  LogLevel,
+  ModelInfo,
+  RemoteSettings,
+  Settings,
+  SnapshotDestination,
+  SnapshotRun,
+  SnapshotSettings,
+  ThreadSummary,
+  UIMessage,
+  UsageStats,
+} from './types';
 import './App.css';
 
 type Theme = 'light' | 'dark';
 type ViewMode = 'chat' | 'settings';
-type SettingsTab = 'personalization' | 'instructions' | 'usage';
+type SettingsTab = 'personalization' | 'instructions' | 'usage' | 'backend' | 'data' | 'logs';
 type ThinkingLevel = 'low' | 'medium' | 'high' | 'xhigh';
 type ThinkingSelection = ThinkingLevel | null;
 
@@ -179,6 +218,948 @@ function PreBlock({ children, ...props }: PreBlockProps) {
   );
 }
 
+const LOG_LEVEL_RANK: Record<LogLevel, number> = { error: 0, warn: 1, info: 2, debug: 3 };
+const LOG_VIEW_LIMIT = 1000;
+
+const logMatches = (entry: LogEntry, level: LogLevel, source: string) =>
+  LOG_LEVEL_RANK[entry.level] <= LOG_LEVEL_RANK[level] &&
+  (!source || entry.source === source || entry.source.startsWith(`${source}:`));
+
+function LogsPanel() {
+  const [entries, setEntries] = useState<LogEntry[]>([]);
+  const [level, setLevel] = useState<LogLevel>('info');
+  const [source, setSource] = useState('');
+  const [follow, setFollow] = useState(true);
+  const viewRef = useRef<HTMLPreElement>(null);
+
+  useEffect(() => {
+    let cancelled = false;
+    let unsubscribe: (() => void) | null = null;
+
+    fetchRecentLogs({ level, source: source || undefined, limit: LOG_VIEW_LIMIT })
+      .then((recent) => {
+        if (!cancelled) setEntries(recent);
+      })
+      .catch(() => {
+        // Host unavailable; leave the view empty.
+      });
+
+    subscribeToLogs((entry) => {
+      if (!logMatches(entry, level, source)) return;
+      setEntries((prev) => [...prev.slice(-(LOG_VIEW_LIMIT - 1)), entry]);
+    }).then((stop) => {
+      if (cancelled) stop();
+      else unsubscribe = stop;
+    });
+
+    return () => {
+      cancelled = true;
+      unsubscribe?.();
+    };
+  }, [level, source]);
+
+  useEffect(() => {
+    if (follow && viewRef.current) {
+      viewRef.current.scrollTop = viewRef.current.scrollHeight;
+    }
+  }, [entries, follow]);
+
+  return (
+    <div className="settings-card">
+      <div className="settings-row">
+        <div>
+          <h3>Logs</h3>
+          <p>Live output from the desktop host and the local API server.</p>
+        </div>
+        <div className="settings-header-actions">
+          <select
+            className="settings-select"
+            value={source}
+            onChange={(e) => setSource(e.target.value)}
+            aria-label="Log source"
+          >
+            <option value="">All sources</option>
+            <option value="host">Host</option>
+            <option value="api">API</option>
+          </select>
+          <select
+            className="settings-select"
+            value={level}
+            onChange={(e) => setLevel(e.target.value as LogLevel)}
+            aria-label="Log level"
+          >
+            <option value="error">Errors</option>
+            <option value="warn">Warnings</option>
+            <option value="info">Info</option>
+            <option value="debug">Debug</option>
+          </select>
+          <button className="button" onClick={() => setFollow((prev) => !prev)}>
+            {follow ? 'Pause' : 'Follow'}
+          </button>
+        </div>
+      </div>
+      <pre ref={viewRef} className="logs-view">
+        {entries.length === 0
+          ? 'No log entries yet.'
+          : entries.map((entry, index) => (
+              <div key={`${entry.timestamp}-${index}`} className={`logs-line ${entry.level}`}>
+                {`${entry.timestamp} ${entry.level.toUpperCase().padEnd(5)} [${entry.source}] ${entry.message}`}
+              </div>
+            ))}
+      </pre>
+    </div>
+  );
+}
+
+const describeError = (err: unknown) => (err instanceof Error ? err.message : String(err));
+
+const splitDomains = (value: string) =>
+  value
+    .split(/[\s,]+/)
+    .map((domain) => domain.trim().toLowerCase())
+    .filter(Boolean);
+
+function ServerConfigCard() {
+  const [path, setPath] = useState<string | null>(null);
+  const [config, setConfig] = useState<HostConfig | null>(null);
+  const [allowDomains, setAllowDomains] = useState('');
+  const [denyDomains, setDenyDomains] = useState('');
+  const [notice, setNotice] = useState<string | null>(null);
+
+  useEffect(() => {
+    fetchHostConfig()
+      .then((result) => {
+        if (!result) return;
+        setPath(result.path);
+        setConfig(result.config);
+        setAllowDomains((result.config.web_fetch.allow_domains ?? []).join(', '));
+        setDenyDomains((result.config.web_fetch.deny_domains ?? []).join(', '));
+      })
+      .catch((err) => setNotice(describeError(err)));
+  }, []);
+
+  if (!config) {
+    return notice ? (
+      <div className="settings-card">
+        <h3>Server configuration</h3>
+        <p>{notice}</p>
+      </div>
+    ) : null;
+  }
+
+  const render = config.web_fetch.render;
+  const updateRender = (patch: Partial<HostConfig['web_fetch']['render']>) =>
+    setConfig({
+      ...config,
+      web_fetch: { ...config.web_fetch, render: { ...render, ...patch } },
+    });
+
+  const handleSave = async () => {
+    const next: HostConfig = {
+      ...config,
+      web_fetch: {
+        ...config.web_fetch,
+        allow_domains: splitDomains(allowDomains),
+        deny_domains: splitDomains(denyDomains),
+      },
+    };
+    try {
+      await updateHostConfig(next);
+      setConfig(next);
+      setNotice('Saved. The API server is restarting with the new settings.');
+    } catch (err) {
+      setNotice(describeError(err));
+    }
+  };
+
+  return (
+    <div className="settings-card">
+      <div className="settings-row">
+        <div>
+          <h3>Server configuration</h3>
+          <p>Options for the built-in API server. Everything else can be set in {path}.</p>
+        </div>
+      </div>
+      <div className="settings-grid">
+        <div className="settings-field">
+          <label>Web fetch allowed domains</label>
+          <input
+            className="settings-input"
+            value={allowDomains}
+            onChange={(e) => setAllowDomains(e.target.value)}
+            placeholder="Any domain"
+          />
+        </div>
+        <div className="settings-field">
+          <label>Web fetch blocked domains</label>
+          <input
+            className="settings-input"
+            value={denyDomains}
+            onChange={(e) => setDenyDomains(e.target.value)}
+            placeholder="None"
+          />
+        </div>
+        <div className="settings-field">
+          <label>JavaScript rendering</label>
+          <select
+            value={render.mode ?? 'off'}
+            onChange={(e) =>
+              updateRender({ mode: e.target.value as NonNullable<typeof render.mode> })
+            }
+          >
+            <option value="off">Off</option>
+            <option value="auto">When needed</option>
+            <option value="always">Always</option>
+          </select>
+        </div>
+        <div className="settings-field">
+          <label>Render service URL</label>
+          <input
+            className="settings-input"
+            value={render.url ?? ''}
+            onChange={(e) => updateRender({ url: e.target.value || undefined })}
+            placeholder="https://render.example.com"
+          />
+        </div>
+        <div className="settings-field">
+          <label>Keep reasoning traces (days)</label>
+          <input
+            className="settings-input"
+            type="number"
+            min={1}
+            value={config.trace.retention_days ?? ''}
+            onChange={(e) =>
+              setConfig({
+                ...config,
+                trace: {
+                  ...config.trace,
+                  retention_days: e.target.value ? Number(e.target.value) : undefined,
+                },
+              })
+            }
+            placeholder="30"
+          />
+        </div>
+        <div className="settings-field">
+          <label>Restart the server above (MB of memory)</label>
+          <input
+            className="settings-input"
+            type="number"
+            min={256}
+            value={config.resources.soft_rss_mb ?? ''}
+            onChange={(e) =>
+              setConfig({
+                ...config,
+                resources: {
+                  ...config.resources,
+                  soft_rss_mb: e.target.value ? Number(e.target.value) : undefined,
+                },
+              })
+            }
+            placeholder="No limit"
+          />
+        </div>
+        <div className="settings-field">
+          <label>Database check at startup</label>
+          <select
+            value={config.database.full_integrity_check ? 'full' : 'quick'}
+            onChange={(e) =>
+              setConfig({
+                ...config,
+                database: {
+                  ...config.database,
+                  full_integrity_check: e.target.value === 'full' || undefined,
+                },
+              })
+            }
+          >
+            <option value="quick">Quick</option>
+            <option value="full">Full (slower)</option>
+          </select>
+        </div>
+      </div>
+      {notice && <p>{notice}</p>}
+      <div className="settings-actions">
+        <button className="button primary" onClick={handleSave}>
+          Save configuration
+        </button>
+      </div>
+    </div>
+  );
+}
+
+const formatSize = (bytes: number) => {
+  if (bytes < 1024 * 1024) return `${Math.max(1, Math.round(bytes / 1024))} KB`;
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+};
+
+const EMPTY_DESTINATIONS: Record<SnapshotDestination['kind'], SnapshotDestination> = {
+  folder: { kind: 'folder', path: '' },
+  s3: { kind: 's3', endpoint: '', region: 'us-east-1', bucket: '', prefix: '', accessKey: '' },
+  webDav: { kind: 'webDav', url: '' },
+};
+
+function SnapshotsCard() {
+  const [settings, setSettings] = useState<SnapshotSettings | null>(null);
+  const [hasSecret, setHasSecret] = useState(false);
+  const [secret, setSecret] = useState('');
+  const [lastRun, setLastRun] = useState<SnapshotRun | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [notice, setNotice] = useState<string | null>(null);
+
+  useEffect(() => {
+    fetchSnapshotSettings()
+      .then((view) => {
+        if (!view) return;
+        setSettings(view.settings);
+        setHasSecret(view.hasSecret);
+        setLastRun(view.lastRun);
+        setBusy(view.running);
+      })
+      .catch((err) => setNotice(describeError(err)));
+  }, []);
+
+  useEffect(() => {
+    let unsubscribe: (() => void) | null = null;
+    let cancelled = false;
+    subscribeToSnapshots((run) => {
+      setLastRun(run);
+      setBusy(false);
+    }).then((stop) => {
+      if (cancelled) stop();
+      else unsubscribe = stop;
+    });
+    return () => {
+      cancelled = true;
+      unsubscribe?.();
+    };
+  }, []);
+
+  if (!settings) return null;
+
+  const destination = settings.destination ?? EMPTY_DESTINATIONS.folder;
+  const updateDestination = (patch: Partial<SnapshotDestination>) =>
+    setSettings({ ...settings, destination: { ...destination, ...patch } as SnapshotDestination });
+  // Secrets never come back from the host; leave them undefined to keep the saved one.
+  const withSecret = (): SnapshotDestination => {
+    if (!secret) return destination;
+    if (destination.kind === 's3') return { ...destination, secretKey: secret };
+    if (destination.kind === 'webDav') return { ...destination, password: secret };
+    return destination;
+  };
+
+  const run = async (action: () => Promise<void>, success: string | null) => {
+    setBusy(true);
+    try {
+      await action();
+      if (success) setNotice(success);
+    } catch (err) {
+      setNotice(describeError(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const numberField = (value: number, onChange: (value: number) => void, min = 1) => (
+    <input
+      className="settings-input"
+      type="number"
+      min={min}
+      value={value}
+      onChange={(e) => onChange(Number(e.target.value))}
+    />
+  );
+
+  return (
+    <div className="settings-card">
+      <div className="settings-row">
+        <div>
+          <h3>Automatic snapshots</h3>
+          <p>
+            {lastRun
+              ? `${lastRun.message} (${new Date(lastRun.finishedAtMs).toLocaleString()})`
+              : 'No snapshot has been taken yet.'}
+          </p>
+        </div>
+        <label className="settings-checkbox">
+          <input
+            type="checkbox"
+            checked={settings.enabled}
+            onChange={(e) => setSettings({ ...settings, enabled: e.target.checked })}
+          />
+          Enabled
+        </label>
+      </div>
+      <div className="settings-grid">
+        <div className="settings-field">
+          <label>Every (hours)</label>
+          {numberField(settings.intervalHours, (intervalHours) =>
+            setSettings({ ...settings, intervalHours }),
+          )}
+        </div>
+        <div className="settings-field">
+          <label>Keep</label>
+          <select
+            value={settings.retention.kind}
+            onChange={(e) =>
+              setSettings({
+                ...settings,
+                retention:
+                  e.target.value === 'calendar'
+                    ? { kind: 'calendar', daily: 7, weekly: 4 }
+                    : { kind: 'keepLast', count: 7 },
+              })
+            }
+          >
+            <option value="keepLast">The most recent snapshots</option>
+            <option value="calendar">Daily and weekly snapshots</option>
+          </select>
+        </div>
+        {settings.retention.kind === 'keepLast' ? (
+          <div className="settings-field">
+            <label>Snapshots to keep</label>
+            {numberField(settings.retention.count, (count) =>
+              setSettings({ ...settings, retention: { kind: 'keepLast', count } }),
+            )}
+          </div>
+        ) : (
+          <>
+            <div className="settings-field">
+              <label>Days to keep</label>
+              {numberField(
+                settings.retention.daily,
+                (daily) =>
+                  setSettings({
+                    ...settings,
+                    retention: { ...settings.retention, daily } as SnapshotSettings['retention'],
+                  }),
+                0,
+              )}
+            </div>
+            <div className="settings-field">
+              <label>Weeks to keep</label>
+              {numberField(
+                settings.retention.weekly,
+                (weekly) =>
+                  setSettings({
+                    ...settings,
+                    retention: { ...settings.retention, weekly } as SnapshotSettings['retention'],
+                  }),
+                0,
+              )}
+            </div>
+          </>
+        )}
+        <div className="settings-field">
+          <label>Destination</label>
+          <select
+            value={destination.kind}
+            onChange={(e) => {
+              const kind = e.target.value as SnapshotDestination['kind'];
+              setSettings({ ...settings, destination: EMPTY_DESTINATIONS[kind] });
+              setSecret('');
+            }}
+          >
+            <option value="folder">Folder or network share</option>
+            <option value="s3">S3-compatible storage</option>
+            <option value="webDav">WebDAV</option>
+          </select>
+        </div>
+        {destination.kind === 'folder' && (
+          <div className="settings-field">
+            <label>Folder</label>
+            <input
+              className="settings-input"
+              value={destination.path}
+              onChange={(e) => updateDestination({ path: e.target.value })}
+              placeholder="/Volumes/backup/pro-chat"
+            />
+          </div>
+        )}
+        {destination.kind === 's3' && (
+          <>
+            <div className="settings-field">
+              <label>Endpoint</label>
+              <input
+                className="settings-input"
+                value={destination.endpoint}
+                onChange={(e) => updateDestination({ endpoint: e.target.value })}
+                placeholder="https://s3.us-east-1.amazonaws.com"
+              />
+            </div>
+            <div className="settings-field">
+              <label>Region</label>
+              <input
+                className="settings-input"
+                value={destination.region}
+                onChange={(e) => updateDestination({ region: e.target.value })}
+              />
+            </div>
+            <div className="settings-field">
+              <label>Bucket</label>
+              <input
+                className="settings-input"
+                value={destination.bucket}
+                onChange={(e) => updateDestination({ bucket: e.target.value })}
+              />
+            </div>
+            <div className="settings-field">
+              <label>Folder in the bucket (optional)</label>
+              <input
+                className="settings-input"
+                value={destination.prefix}
+                onChange={(e) => updateDestination({ prefix: e.target.value })}
+              />
+            </div>
+            <div className="settings-field">
+              <label>Access key</label>
+              <input
+                className="settings-input"
+                value={destination.accessKey}
+                onChange={(e) => updateDestination({ accessKey: e.target.value })}
+              />
+            </div>
+          </>
+        )}
+        {destination.kind === 'webDav' && (
+          <>
+            <div className="settings-field">
+              <label>URL</label>
+              <input
+                className="settings-input"
+                value={destination.url}
+                onChange={(e) => updateDestination({ url: e.target.value })}
+                placeholder="https://cloud.example.com/remote.php/dav/files/me/pro-chat"
+              />
+            </div>
+            <div className="settings-field">
+              <label>Username (optional)</label>
+              <input
+                className="settings-input"
+                value={destination.username ?? ''}
+                onChange={(e) => updateDestination({ username: e.target.value || undefined })}
+              />
+            </div>
+          </>
+        )}
+        {destination.kind !== 'folder' && (
+          <div className="settings-field">
+            <label>{destination.kind === 's3' ? 'Secret key' : 'Password'}</label>
+            <input
+              className="settings-input"
+              type="password"
+              value={secret}
+              onChange={(e) => setSecret(e.target.value)}
+              placeholder={hasSecret ? 'Leave blank to keep the saved one' : ''}
+            />
+          </div>
+        )}
+      </div>
+      {notice && <p>{notice}</p>}
+      <div className="settings-actions">
+        <button
+          className="button"
+          disabled={busy}
+          onClick={() =>
+            run(() => testSnapshotDestination(withSecret()), 'The destination works.')
+          }
+        >
+          Test destination
+        </button>
+        <button
+          className="button"
+          disabled={busy}
+          onClick={() =>
+            run(async () => {
+              setNotice('Taking a snapshot…');
+              const result = await runSnapshot();
+              if (result) setLastRun(result);
+              setNotice(null);
+            }, null)
+          }
+        >
+          Snapshot now
+        </button>
+        <button
+          className="button primary"
+          disabled={busy}
+          onClick={() =>
+            run(async () => {
+              await saveSnapshotSettings({ ...settings, destination: withSecret() });
+              if (secret) setHasSecret(true);
+              setSecret('');
+            }, 'Snapshot settings saved.')
+          }
+        >
+          Save
+        </button>
+      </div>
+    </div>
+  );
+}
+
+function DataPanel() {
+  const [backups, setBackups] = useState<BackupInfo[]>([]);
+  const [restorePath, setRestorePath] = useState('');
+  const [busy, setBusy] = useState(false);
+  const [notice, setNotice] = useState<string | null>(null);
+
+  const reload = useCallback(() => {
+    listBackups()
+      .then(setBackups)
+      .catch((err) => setNotice(describeError(err)));
+  }, []);
+
+  useEffect(() => {
+    reload();
+  }, [reload]);
+
+  const handleBackup = async () => {
+    setBusy(true);
+    setNotice('Backing up…');
+    try {
+      const backup = await createBackup();
+      setNotice(backup ? `Saved ${backup.path}.` : null);
+      reload();
+    } catch (err) {
+      setNotice(describeError(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleRestore = async (path: string) => {
+    const confirmed = window.confirm(
+      `Replace all chats, attachments and memory with the contents of ${path}? ` +
+        'Anything not in the backup will be lost.',
+    );
+    if (!confirmed) return;
+    setBusy(true);
+    setNotice('Restoring…');
+    try {
+      await restoreBackup(path);
+      // Everything on screen came from the old data.
+      window.location.reload();
+    } catch (err) {
+      setNotice(describeError(err));
+      setBusy(false);
+    }
+  };
+
+  return (
+    <>
+      <div className="settings-card">
+        <div className="settings-row">
+          <div>
+            <h3>Backups</h3>
+            <p>
+              A backup holds the database, attachments and memory in a single archive.
+              Restoring one replaces all of them and restarts the API server.
+            </p>
+          </div>
+          <div className="settings-header-actions">
+            <button className="button primary" onClick={handleBackup} disabled={busy}>
+              Back up now
+            </button>
+          </div>
+        </div>
+        {backups.length === 0 ? (
+          <p>No backups yet.</p>
+        ) : (
+          <div className="settings-grid">
+            {backups.map((backup) => (
+              <div className="settings-row" key={backup.path}>
+                <div>
+                  <strong>{new Date(backup.modifiedMs).toLocaleString()}</strong>
+                  <p>
+                    {formatSize(backup.size)} · {backup.path}
+                  </p>
+                </div>
+                <button
+                  className="button"
+                  onClick={() => handleRestore(backup.path)}
+                  disabled={busy}
+                >
+                  Restore
+                </button>
+              </div>
+            ))}
+          </div>
+        )}
+        <div className="settings-field">
+          <label>Restore from another file</label>
+          <input
+            className="settings-input"
+            value={restorePath}
+            onChange={(e) => setRestorePath(e.target.value)}
+            placeholder="/path/to/pro-chat-backup.tar.gz"
+          />
+        </div>
+        {notice && <p>{notice}</p>}
+        <div className="settings-actions">
+          <button
+            className="button"
+            onClick={() => handleRestore(restorePath.trim())}
+            disabled={busy || !restorePath.trim()}
+          >
+            Restore from file
+          </button>
+        </div>
+      </div>
+      <SnapshotsCard />
+    </>
+  );
+}
+
+function BackendPanel() {
+  const [settings, setSettings] = useState<RemoteSettings | null>(null);
+  const [editing, setEditing] = useState<string | null>(null);
+  const [name, setName] = useState('');
+  const [url, setUrl] = useState('');
+  const [token, setToken] = useState('');
+  const [notice, setNotice] = useState<string | null>(null);
+
+  const reload = useCallback(() => {
+    fetchRemoteSettings()
+      .then(setSettings)
+      .catch((err) => setNotice(describeError(err)));
+  }, []);
+
+  useEffect(() => {
+    reload();
+  }, [reload]);
+
+  const editProfile = (profileName: string | null) => {
+    const profile = settings?.profiles.find((entry) => entry.name === profileName);
+    setEditing(profile?.name ?? null);
+    setName(profile?.name ?? '');
+    setUrl(profile?.url ?? '');
+    setToken('');
+    setNotice(null);
+  };
+
+  const run = async (action: () => Promise<void>, success: string) => {
+    try {
+      await action();
+      setNotice(success);
+      reload();
+    } catch (err) {
+      setNotice(describeError(err));
+    }
+  };
+
+  if (!settings) {
+    return (
+      <div className="settings-card">
+        <h3>Backend</h3>
+        <p>{notice ?? 'Backend settings are only available in the desktop app.'}</p>
+      </div>
+    );
+  }
+
+  const savedToken = settings.profiles.some((entry) => entry.name === editing && entry.hasToken);
+
+  return (
+    <>
+      <div className="settings-card">
+        <div className="settings-row">
+          <div>
+            <h3>Backend</h3>
+            <p>
+              {settings.currentUrl
+                ? `Connected to ${settings.currentUrl}.`
+                : 'Using the built-in API server on this computer.'}{' '}
+              Changes apply the next time pro-chat starts.
+            </p>
+          </div>
+          <div className="settings-header-actions">
+            <select
+              className="settings-select"
+              value={settings.active ?? ''}
+              onChange={(e) =>
+                run(
+                  () => setActiveRemoteProfile(e.target.value || null),
+                  'Saved. Restart pro-chat to switch.',
+                )
+              }
+              aria-label="Backend used at launch"
+            >
+              <option value="">Built-in server</option>
+              {settings.profiles.map((profile) => (
+                <option key={profile.name} value={profile.name}>
+                  {profile.name}
+                </option>
+              ))}
+            </select>
+          </div>
+        </div>
+      </div>
+
+      <div className="settings-card">
+        <div className="settings-row">
+          <div>
+            <h3>Remote profiles</h3>
+            <p>
+              A shared pro-chat API server, with an optional access token sent as a bearer token.
+            </p>
+          </div>
+          <div className="settings-header-actions">
+            <select
+              className="settings-select"
+              value={editing ?? ''}
+              onChange={(e) => editProfile(e.target.value || null)}
+              aria-label="Profile to edit"
+            >
+              <option value="">New profile</option>
+              {settings.profiles.map((profile) => (
+                <option key={profile.name} value={profile.name}>
+                  {profile.name}
+                </option>
+              ))}
+            </select>
+          </div>
+        </div>
+        <div className="settings-grid">
+          <div className="settings-field">
+            <label>Name</label>
+            <input
+              className="settings-input"
+              value={name}
+              onChange={(e) => setName(e.target.value)}
+              disabled={editing !== null}
+              placeholder="Team server"
+            />
+          </div>
+          <div className="settings-field">
+            <label>API URL</label>
+            <input
+              className="settings-input"
+              value={url}
+              onChange={(e) => setUrl(e.target.value)}
+              placeholder="https://chat.example.com"
+            />
+          </div>
+          <div className="settings-field">
+            <label>Access token (optional)</label>
+            <input
+              className="settings-input"
+              type="password"
+              value={token}
+              onChange={(e) => setToken(e.target.value)}
+              placeholder={savedToken ? 'Leave blank to keep the saved token' : ''}
+            />
+          </div>
+        </div>
+        {notice && <p>{notice}</p>}
+        <div className="settings-actions">
+          <button
+            className="button"
+            onClick={() =>
+              run(
+                () => checkRemote(url, token, editing ?? undefined),
+                'The server is reachable.',
+              )
+            }
+          >
+            Test connection
+          </button>
+          {editing && (
+            <button
+              className="button"
+              onClick={() =>
+                run(async () => {
+                  await deleteRemoteProfile(editing);
+                  editProfile(null);
+                }, 'Profile deleted.')
+              }
+            >
+              Delete
+            </button>
+          )}
+          <button
+            className="button primary"
+            onClick={() =>
+              run(async () => {
+                await saveRemoteProfile(name, url, token || (editing ? undefined : ''));
+                setEditing(name.trim());
+                setToken('');
+              }, 'Profile saved.')
+            }
+          >
+            Save profile
+          </button>
+        </div>
+      </div>
+
+      {!settings.currentUrl && <ServerConfigCard />}
+    </>
+  );
+}
+
+const API_STATE_MESSAGES: Record<Exclude<ApiStatus['state'], 'running'>, string> = {
+  restarting: 'The local API server stopped unexpectedly and is restarting…',
+  failed: 'The local API server keeps crashing and has been stopped.',
+  stopped: 'The local API server is not running.',
+};
+
+function ConnectionBanner() {
+  const [status, setStatus] = useState<ApiStatus | null>(null);
+  const [isRestarting, setIsRestarting] = useState(false);
+
+  useEffect(() => {
+    let cancelled = false;
+    let unsubscribe: (() => void) | null = null;
+
+    fetchApiStatus()
+      .then((current) => {
+        if (!cancelled) setStatus(current);
+      })
+      .catch(() => {
+        // Not running inside the desktop shell.
+      });
+
+    subscribeToApiStatus((next) => setStatus(next)).then((stop) => {
+      if (cancelled) stop();
+      else unsubscribe = stop;
+    });
+
+    return () => {
+      cancelled = true;
+      unsubscribe?.();
+    };
+  }, []);
+
+  const handleRestart = async () => {
+    setIsRestarting(true);
+    try {
+      const next = await restartApi();
+      if (next) setStatus(next);
+    } catch (error) {
+      console.error('Failed to restart API server', error);
+    } finally {
+      setIsRestarting(false);
+    }
+  };
+
+  // No port or socket means the host never launched a sidecar (e.g. a remote backend).
+  const launched = status && (status.port !== null || status.socket !== null);
+  if (!status || status.state === 'running' || !launched) return null;
+
+  return (
+    <div className={`connection-banner ${status.state}`} role="status">
+      <span>
+        {API_STATE_MESSAGES[status.state]}
+        {status.lastExitCode !== null && ` (exit code ${status.lastExitCode})`}
+      </span>
+      {status.state !== 'restarting' && (
+        <button className="button" onClick={handleRestart} disabled={isRestarting}>
+          {isRestarting ? 'Restarting…' : 'Restart backend'}
+        </button>
+      )}
+    </div>
+  );
+}
+
 export default function App() {
   const [threads, setThreads] = useState<ThreadSummary[]>([]);
   const [activeThreadId, setActiveThreadId] = useState<string | null>(null);
@@ -1201,6 +2182,7 @@ export default function App() {
       onDragOver={handleDragOver}
       onDrop={handleDrop}
     >
+        <ConnectionBanner />
         {isDragActive && (
           <div className="drop-overlay" aria-hidden="true">
             Drop files to attach
@@ -1595,6 +2577,24 @@ export default function App() {
               >
                 Usage
               </button>
+              <button
+                className={`settings-tab ${settingsTab === 'backend' ? 'active' : ''}`}
+                onClick={() => setSettingsTab('backend')}
+              >
+                Backend
+              </button>
+              <button
+                className={`settings-tab ${settingsTab === 'data' ? 'active' : ''}`}
+                onClick={() => setSettingsTab('data')}
+              >
+                Data
+              </button>
+              <button
+                className={`settings-tab ${settingsTab === 'logs' ? 'active' : ''}`}
+                onClick={() => setSettingsTab('logs')}
+              >
+                Logs
+              </button>
             </div>
 
             <div className="settings-content">
@@ -2142,6 +3142,12 @@ export default function App() {
                 </>
               )}
 
+              {settingsTab === 'backend' && <BackendPanel />}
+
+              {settingsTab === 'data' && <DataPanel />}
+
+              {settingsTab === 'logs' && <LogsPanel />}
+
             </div>
           </section>
         )}
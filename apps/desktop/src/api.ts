@@ -12,8 +12,16 @@ import type {
 } from './types';
 
 const JSON_HEADERS = { 'Content-Type': 'application/json' };
-const resolveApiBase = () => {
-  if (typeof window === 'undefined') return 'http://127.0.0.1:8787';
+const FALLBACK_API_BASE = 'http://127.0.0.1:8787';
+
+type ApiConnection = {
+  baseUrl: string;
+  headers: Record<string, string>;
+  streamViaHost: boolean;
+};
+
+const originApiBase = () => {
+  if (typeof window === 'undefined') return FALLBACK_API_BASE;
   const origin = window.location.origin;
   if (
     origin.startsWith('http://localhost:5173') ||
@@ -21,16 +29,131 @@ const resolveApiBase = () => {
   ) {
     return '';
   }
-  return 'http://127.0.0.1:8787';
+  return FALLBACK_API_BASE;
+};
+
+const resolveConnection = async (): Promise<ApiConnection> => {
+  if (typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window) {
+    try {
+      // The desktop host knows whether it runs its own API (on a port picked at
+      // launch) or talks to a remote one, and which headers that needs.
+      const { invoke } = await import('@tauri-apps/api/core');
+      const connection = await invoke<{
+        baseUrl: string | null;
+        headers: Record<string, string>;
+        streamViaHost: boolean;
+      }>('api_connection');
+      return {
+        baseUrl: connection.baseUrl ?? originApiBase(),
+        headers: connection.headers,
+        streamViaHost: connection.streamViaHost,
+      };
+    } catch {
+      // Fall through to origin-based detection.
+    }
+  }
+  return { baseUrl: originApiBase(), headers: {}, streamViaHost: false };
 };
 
-const API_BASE = resolveApiBase().replace(/\/+$/, '');
-const buildUrl = (path: string) => (API_BASE ? `${API_BASE}${path}` : path);
+let connectionPromise: Promise<ApiConnection> | null = null;
+const getConnection = () => {
+  if (!connectionPromise) {
+    connectionPromise = resolveConnection().then((connection) => ({
+      ...connection,
+      baseUrl: connection.baseUrl.replace(/\/+$/, ''),
+    }));
+  }
+  return connectionPromise;
+};
+
+// Responses from the host's prochat-api:// protocol arrive in one piece, so when
+// the API sits behind it the chat streams are relayed over an IPC channel.
+const HOST_STREAMED_PATHS = new Set(['/api/chat/stream', '/api/chat/resume']);
+
+type HostStreamMessage =
+  | { type: 'head'; status: number; headers: [string, string][] }
+  | { type: 'chunk'; data: string }
+  | { type: 'end' }
+  | { type: 'error'; message: string };
+
+async function hostStream(
+  url: string,
+  options: RequestInit,
+  headers: Record<string, string>,
+): Promise<Response> {
+  const { Channel, invoke } = await import('@tauri-apps/api/core');
+  const encoder = new TextEncoder();
+  let streamId: number | null = null;
+  let finished = false;
+  let controller!: ReadableStreamDefaultController<Uint8Array>;
+  const body = new ReadableStream<Uint8Array>({
+    start(c) {
+      controller = c;
+    },
+    cancel() {
+      finished = true;
+      if (streamId !== null) void invoke('api_stream_cancel', { id: streamId });
+    },
+  });
+
+  return new Promise<Response>((resolve, reject) => {
+    const fail = (error: Error) => {
+      if (finished) return;
+      finished = true;
+      reject(error);
+      controller.error(error);
+    };
+    const channel = new Channel<HostStreamMessage>();
+    channel.onmessage = (message) => {
+      if (finished) return;
+      if (message.type === 'head') {
+        resolve(new Response(body, { status: message.status, headers: message.headers }));
+      } else if (message.type === 'chunk') {
+        controller.enqueue(encoder.encode(message.data));
+      } else if (message.type === 'end') {
+        finished = true;
+        controller.close();
+      } else {
+        fail(new Error(message.message));
+      }
+    };
+
+    const { signal } = options;
+    const abort = () => {
+      fail(new DOMException('The request was aborted.', 'AbortError'));
+      if (streamId !== null) void invoke('api_stream_cancel', { id: streamId });
+    };
+    if (signal?.aborted) {
+      abort();
+      return;
+    }
+    signal?.addEventListener('abort', abort, { once: true });
+
+    invoke<number>('api_stream', {
+      method: options.method ?? 'GET',
+      path: url,
+      headers: { ...headers, ...Object.fromEntries(new Headers(options.headers).entries()) },
+      body: typeof options.body === 'string' ? options.body : null,
+      channel,
+    }).then(
+      (id) => {
+        streamId = id;
+        if (signal?.aborted) void invoke('api_stream_cancel', { id });
+      },
+      (error) => fail(new Error(String(error))),
+    );
+  });
+}
 
 async function apiFetch(url: string, options: RequestInit = {}): Promise<Response> {
-  return fetch(buildUrl(url), {
+  const { baseUrl, headers, streamViaHost } = await getConnection();
+  if (streamViaHost && HOST_STREAMED_PATHS.has(url)) {
+    return hostStream(url, options, headers);
+  }
+  return fetch(baseUrl ? `${baseUrl}${url}` : url, {
     ...options,
     headers: {
+      ...headers,
       ...options.headers,
     },
   });
@@ -127,7 +250,7 @@ export async function uploadFiles(threadId: string, files: FileList): Promise<Up
     formData.append('files', file);
   });
 
-  const res = await fetch(buildUrl('/api/uploads'), {
+  const res = await apiFetch('/api/uploads', {
     method: 'POST',
     body: formData,
   });
@@ -177,7 +300,7 @@ export async function streamChat(
   callbacks: StreamCallbacks,
 ) {
   const { signal, ...body } = payload;
-  const response = await fetch(buildUrl('/api/chat/stream'), {
+  const response = await apiFetch('/api/chat/stream', {
     method: 'POST',
     headers: JSON_HEADERS,
     body: JSON.stringify(body),
@@ -231,7 +354,7 @@ export async function streamChat(
 }
 
 export async function checkActiveStream(threadId: string): Promise<CheckActiveStreamResponse> {
-  const res = await fetch(buildUrl(`/api/threads/${threadId}/active-stream`));
+  const res = await apiFetch(`/api/threads/${threadId}/active-stream`);
   return handleJson<CheckActiveStreamResponse>(res);
 }
 
@@ -240,7 +363,7 @@ export async function resumeStream(
   callbacks: StreamCallbacks,
   signal?: AbortSignal,
 ) {
-  const response = await fetch(buildUrl('/api/chat/resume'), {
+  const response = await apiFetch('/api/chat/resume', {
     method: 'POST',
     headers: JSON_HEADERS,
     body: JSON.stringify({ streamId }),
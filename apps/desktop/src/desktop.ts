@@ -0,0 +1,154 @@
+import type {
+  ApiStatus,
+  BackupInfo,
+  BackupManifest,
+  HostConfig,
+  LogEntry,
+  LogLevel,
+  RemoteSettings,
+  SnapshotDestination,
+  SnapshotRun,
+  SnapshotSettings,
+  SnapshotView,
+} from './types';
+
+// Bridges to the Tauri host. Every helper is a no-op outside the desktop shell
+// so the UI still runs in a plain browser against `npm run dev`.
+const isDesktop = () => typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window;
+
+export type LogFilter = {
+  level?: LogLevel;
+  source?: string;
+  limit?: number;
+};
+
+export async function fetchRecentLogs(filter: LogFilter = {}): Promise<LogEntry[]> {
+  if (!isDesktop()) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<LogEntry[]>('recent_logs', filter);
+}
+
+export async function subscribeToLogs(onEntry: (entry: LogEntry) => void): Promise<() => void> {
+  if (!isDesktop()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<LogEntry>('logs://entry', (event) => onEntry(event.payload));
+}
+
+export async function fetchApiStatus(): Promise<ApiStatus | null> {
+  if (!isDesktop()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<ApiStatus>('api_status');
+}
+
+export async function restartApi(): Promise<ApiStatus | null> {
+  if (!isDesktop()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<ApiStatus>('restart_api');
+}
+
+export async function subscribeToApiStatus(
+  onStatus: (status: ApiStatus) => void,
+): Promise<() => void> {
+  if (!isDesktop()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<ApiStatus>('api://status', (event) => onStatus(event.payload));
+}
+
+export async function fetchRemoteSettings(): Promise<RemoteSettings | null> {
+  if (!isDesktop()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<RemoteSettings>('remote_settings');
+}
+
+// `token: undefined` keeps a profile's saved token; an empty string clears it.
+export async function saveRemoteProfile(name: string, url: string, token?: string): Promise<void> {
+  if (!isDesktop()) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('save_remote_profile', { name, url, token: token ?? null });
+}
+
+export async function deleteRemoteProfile(name: string): Promise<void> {
+  if (!isDesktop()) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('delete_remote_profile', { name });
+}
+
+export async function setActiveRemoteProfile(name: string | null): Promise<void> {
+  if (!isDesktop()) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('set_active_remote_profile', { name });
+}
+
+// Resolves when the server answers its health check; rejects with the reason otherwise.
+export async function checkRemote(url: string, token?: string, profile?: string): Promise<void> {
+  if (!isDesktop()) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('check_remote', { url, token: token || null, profile: profile ?? null });
+}
+
+export async function fetchHostConfig(): Promise<{ path: string; config: HostConfig } | null> {
+  if (!isDesktop()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<{ path: string; config: HostConfig }>('host_config');
+}
+
+// The host restarts the API server once the file is saved.
+export async function updateHostConfig(config: HostConfig): Promise<void> {
+  if (!isDesktop()) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('update_host_config', { config });
+}
+
+export async function listBackups(): Promise<BackupInfo[]> {
+  if (!isDesktop()) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<BackupInfo[]>('list_backups');
+}
+
+// Without a directory the backup goes into the app's own `backups` folder.
+export async function createBackup(directory?: string): Promise<BackupInfo | null> {
+  if (!isDesktop()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<BackupInfo>('create_backup', { directory: directory || null });
+}
+
+// Stops the API server, replaces all local data with the archive's and restarts it.
+export async function restoreBackup(path: string): Promise<BackupManifest | null> {
+  if (!isDesktop()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<BackupManifest>('restore_backup', { path });
+}
+
+export async function fetchSnapshotSettings(): Promise<SnapshotView | null> {
+  if (!isDesktop()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<SnapshotView>('snapshot_settings');
+}
+
+// A destination secret left undefined keeps the saved one; an empty string clears it.
+export async function saveSnapshotSettings(settings: SnapshotSettings): Promise<void> {
+  if (!isDesktop()) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('save_snapshot_settings', { settings });
+}
+
+export async function runSnapshot(): Promise<SnapshotRun | null> {
+  if (!isDesktop()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<SnapshotRun>('run_snapshot');
+}
+
+// Resolves once a test file could be written, listed and deleted at the destination.
+export async function testSnapshotDestination(destination: SnapshotDestination): Promise<void> {
+  if (!isDesktop()) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('test_snapshot_destination', { destination });
+}
+
+export async function subscribeToSnapshots(
+  onRun: (run: SnapshotRun) => void,
+): Promise<() => void> {
+  if (!isDesktop()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<SnapshotRun>('snapshots://status', (event) => onRun(event.payload));
+}
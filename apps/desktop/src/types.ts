@@ -110,3 +110,135 @@ export type CheckActiveStreamResponse = {
   hasActiveStream: boolean;
   stream?: ActiveStreamInfo;
 };
+
+export type LogLevel = 'error' | 'warn' | 'info' | 'debug';
+
+export type LogEntry = {
+  timestamp: string;
+  level: LogLevel;
+  source: string;
+  message: string;
+};
+
+export type ApiState = 'stopped' | 'running' | 'restarting' | 'failed';
+
+export type ApiRestartRecord = {
+  exitCode: number | null;
+  exitedAtMs: number;
+  backoffMs: number;
+};
+
+export type ApiStatus = {
+  state: ApiState;
+  pid: number | null;
+  port: number | null;
+  socket: string | null;
+  uptimeMs: number | null;
+  restartCount: number;
+  lastExitCode: number | null;
+  restarts: ApiRestartRecord[];
+};
+
+export type RemoteProfileSummary = {
+  name: string;
+  url: string;
+  hasToken: boolean;
+};
+
+export type RemoteSettings = {
+  active: string | null;
+  currentProfile: string | null;
+  currentUrl: string | null;
+  profiles: RemoteProfileSummary[];
+};
+
+export type HostConfig = {
+  web_fetch: {
+    allow_domains?: string[];
+    deny_domains?: string[];
+    max_redirects?: number;
+    render: {
+      mode?: 'off' | 'auto' | 'always';
+      url?: string;
+      header?: string;
+      token?: string;
+    };
+  };
+  trace: {
+    max_events?: number;
+    max_chars?: number;
+    max_sources?: number;
+    max_source_chars?: number;
+    max_source_snippet_chars?: number;
+    retention_days?: number;
+  };
+  openrouter: {
+    app_url?: string;
+    app_name?: string;
+  };
+  resources: {
+    max_address_space_mb?: number;
+    max_data_mb?: number;
+    max_open_files?: number;
+    nice?: number;
+    soft_rss_mb?: number;
+  };
+  database: {
+    full_integrity_check?: boolean;
+  };
+  watchdog: {
+    interval_secs?: number;
+    failure_threshold?: number;
+    max_deferral_secs?: number;
+  };
+};
+
+export type BackupInfo = {
+  path: string;
+  size: number;
+  modifiedMs: number;
+};
+
+export type BackupManifest = {
+  format: number;
+  appVersion: string;
+  createdAt: string;
+  files: { path: string; size: number; sha256: string }[];
+};
+
+export type SnapshotDestination =
+  | { kind: 'folder'; path: string }
+  | {
+      kind: 's3';
+      endpoint: string;
+      region: string;
+      bucket: string;
+      prefix: string;
+      accessKey: string;
+      secretKey?: string;
+    }
+  | { kind: 'webDav'; url: string; username?: string; password?: string };
+
+export type SnapshotRetention =
+  | { kind: 'keepLast'; count: number }
+  | { kind: 'calendar'; daily: number; weekly: number };
+
+export type SnapshotSettings = {
+  enabled: boolean;
+  intervalHours: number;
+  retention: SnapshotRetention;
+  destination: SnapshotDestination | null;
+};
+
+export type SnapshotRun = {
+  finishedAtMs: number;
+  ok: boolean;
+  message: string;
+};
+
+export type SnapshotView = {
+  settings: SnapshotSettings;
+  hasSecret: boolean;
+  lastRun: SnapshotRun | null;
+  running: boolean;
+};
@@ -13,5 +13,7 @@ fi
 
 cp "$NODE_PATH" "$BIN_DIR/node"
 chmod +x "$BIN_DIR/node"
+# build.rs embeds this (with checksums) so the app can verify the runtime at launch.
+"$BIN_DIR/node" --version > "$BIN_DIR/node.version"
 
-echo "[prepare-tauri] bundled node from $NODE_PATH" >&2
+echo "[prepare-tauri] bundled node $(cat "$BIN_DIR/node.version") from $NODE_PATH" >&2